@@ -6,6 +6,7 @@
 use abscissa_core::testing::CmdRunner;
 use once_cell::sync::Lazy;
 
+/// Executes the generated application binary via `cargo run`
 pub static RUNNER: Lazy<CmdRunner> = Lazy::new(|| CmdRunner::default());
 
 #[test]
@@ -19,6 +19,7 @@ const TEST_COMMANDS: &[&str] = &[
     "clippy",
 ];
 
+/// Runner for `cargo` commands executed against the generated application
 pub static RUNNER: Lazy<CmdRunner> = Lazy::new(|| {
     let mut runner = CmdRunner::new("cargo");
     runner.exclusive();
@@ -55,7 +56,7 @@ fn generate_app(path: &Path) {
 
     // Run `abscissa new` to generate the app
     CmdRunner::default()
-        .args(&[
+        .args([
             "new",
             &path.display().to_string(),
             "--force",
@@ -74,7 +75,7 @@ fn generate_app(path: &Path) {
         let test_file = entry.unwrap().path();
         fs::copy(
             &test_file,
-            app_test_dir.join(&test_file.file_name().unwrap()),
+            app_test_dir.join(test_file.file_name().unwrap()),
         )
         .unwrap();
     }
@@ -88,7 +89,7 @@ fn generate_subcommand(path: &Path) {
     let name = "foo-bar-baz";
 
     CmdRunner::default()
-        .args(&[
+        .args([
             "gen",
             "cmd",
             "--manifest-path",
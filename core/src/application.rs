@@ -1,25 +1,36 @@
 //! Trait for representing an Abscissa application and it's lifecycle
 
 pub mod cell;
+mod crash;
 pub(crate) mod exit;
 mod name;
 mod state;
 
-pub use self::{cell::AppCell, exit::fatal_error, name::Name, state::State};
+pub use self::{
+    cell::{AppCell, StaticApp},
+    crash::CRASH_EXIT_CODE,
+    exit::fatal_error,
+    name::Name,
+    state::State,
+};
 
+#[cfg(all(unix, feature = "signals"))]
+use crate::signal::Signal;
 use crate::{
     command::Command,
     component::Component,
-    config::{self, Config, Configurable},
-    path::{AbsPathBuf, ExePath, RootPath},
-    runnable::Runnable,
-    shutdown::Shutdown,
+    config::{self, Config, Configurable, Loader, Migrations, Provenance, UnknownKeys},
+    error::ExitCode,
+    path::{self, ExePath, PathBuf, RootPath},
+    runnable::TryRunnable,
+    shutdown::{Shutdown, ESCALATION_POLL_INTERVAL},
     terminal::{component::Terminal, ColorChoice},
     trace::{self, Tracing},
     FrameworkError,
     FrameworkErrorKind::*,
 };
-use std::{env, path::Path, process, vec};
+use serde::Serialize;
+use std::{env, process, thread, time::Duration, vec};
 
 /// Application types implementing this trait own global application state,
 /// including configuration and arbitrary other values stored within
@@ -44,22 +55,50 @@ pub trait Application: Default + Sized + 'static {
 
     /// Run application with the given command-line arguments and running the
     /// appropriate `Command` type.
+    ///
+    /// If initialization fails or the command returns an error, the
+    /// application exits with the error's [`ExitCode`] (after shutting down
+    /// its components gracefully in the latter case).
     fn run<I>(app_cell: &'static AppCell<Self>, args: I)
     where
+        Self: Send + Sync,
         I: IntoIterator<Item = String>,
     {
         // Parse command line options
-        let command = Self::Cmd::parse_args(args);
+        let args = args.into_iter().collect::<Vec<_>>();
+        let command = Self::Cmd::parse_args(args.clone());
 
         // Initialize application
         let mut app = Self::default();
-        app.init(&command).unwrap_or_else(|e| fatal_error(&app, &e));
+
+        // Shut down and write a crash report if the app panics (including
+        // while it's being initialized)
+        crash::install_hook(app_cell, app.crash_reports_dir(), args);
+
+        if let Err(e) = app.init(&command) {
+            exit::print_fatal_error(&app, &e);
+            process::exit(e.exit_code());
+        }
+
+        // Keep the command around after booting, e.g. to reload config
+        app.state().set_command(command);
         app_cell.set_once(app);
+        let command = app_cell.state().command().expect("command not set");
 
-        // Run the command
-        command.run();
+        // Keep handling crashes if a component replaced the panic hook
+        crash::reinstall_hook();
+
+        // Handle signals (e.g. Ctrl-C) by shutting down
+        #[cfg(all(unix, feature = "signals"))]
+        crate::signal::install(app_cell).unwrap_or_else(|e| fatal_error(&**app_cell, &e));
+
+        // Run the command, and exit gracefully (with the exit code for the
+        // error it returned, if any)
+        if let Err(e) = command.try_run() {
+            exit::print_fatal_error(&**app_cell, &e);
+            app_cell.shutdown_with_exit_code(Shutdown::Graceful, e.exit_code());
+        }
 
-        // Exit gracefully
         app_cell.shutdown(Shutdown::Graceful);
     }
 
@@ -92,11 +131,10 @@ pub trait Application: Default + Sized + 'static {
         self.register_components(command)?;
 
         // Load configuration
-        let config = command
-            .config_path()
-            .map(|path| self.load_config(&path))
-            .transpose()?
-            .unwrap_or_default();
+        let loader = self.config_loader(command);
+        let (config, provenance, table) = loader.load_with_table()?;
+        self.state().set_config_provenance(provenance, table);
+        self.state().set_config_profile(loader.selected_profile());
 
         // Fire callback regardless of whether any config was loaded to
         // in order to signal state in the application lifecycle
@@ -112,23 +150,134 @@ pub trait Application: Default + Sized + 'static {
         command: &Self::Cmd,
     ) -> Result<Vec<Box<dyn Component<Self>>>, FrameworkError> {
         let terminal = Terminal::new(self.term_colors(command));
+
+        // The terminal replaces the panic hook with `color-eyre`'s
+        crash::reinstall_hook();
         let tracing = Tracing::new(self.tracing_config(command), self.term_colors(command))
             .expect("tracing subsystem failed to initialize");
 
         Ok(vec![Box::new(terminal), Box::new(tracing)])
     }
 
-    /// Load configuration from the given path.
+    /// [`Provenance`] of each value in this application's current
+    /// configuration, as recorded when it was loaded (or last reloaded),
+    /// including overrides applied by `Configurable::process_config`.
+    fn config_provenance(&self) -> Result<Provenance, FrameworkError>
+    where
+        Self::Cfg: Serialize,
+    {
+        let (mut provenance, table) = self.state().config_provenance();
+
+        // Values which differ from the config as loaded were overridden
+        let loaded = match table {
+            Some(table) => toml::Value::Table(table).try_into::<Self::Cfg>().ok(),
+            None => Some(Self::Cfg::default()),
+        };
+
+        if let Some(loaded) = loaded {
+            provenance.record_overrides(
+                &config::provenance::to_table(&loaded)?,
+                &config::provenance::to_table(&*self.config())?,
+            );
+        }
+
+        Ok(provenance)
+    }
+
+    /// Migrations between the versions of this application's configuration
+    /// (see the [`config::migration`] module).
+    ///
+    /// By default there are none, i.e. the configuration is unversioned.
+    fn config_migrations(&self) -> Migrations {
+        Migrations::new()
+    }
+
+    /// How to handle unknown keys in this application's configuration (see
+    /// the [`config::unknown_keys`] module).
+    ///
+    /// By default this is `None`, i.e. unknown keys are handled by the
+    /// configuration type (ignored unless it denies unknown fields).
+    fn config_unknown_keys(&self) -> Option<UnknownKeys> {
+        None
+    }
+
+    /// Does this application's configuration contain profiles (see the
+    /// [`config::profile`] module)?
+    ///
+    /// By default this is `false`, i.e. profiles are only handled when one is
+    /// selected by `config_profile`, and a top-level `profile` key is
+    /// otherwise left to the configuration type.
+    fn config_profiles(&self) -> bool {
+        false
+    }
+
+    /// Prefix of the environment variables overlaid onto this application's
+    /// configuration (see the [`config::env`] module).
+    ///
+    /// By default this is `None`, i.e. environment variables are ignored.
+    /// Return e.g. `Some(config::env::default_prefix(self.name()))` to
+    /// overlay variables named after the application (e.g.
+    /// `MY_APP__SECTION__KEY` for an app named `my-app`).
+    fn config_env_prefix(&self) -> Option<String> {
+        None
+    }
+
+    /// Name of the configuration profile to merge over the rest of the
+    /// configuration (see the [`config::profile`] module).
+    ///
+    /// By default this is the command's `config_profile`, falling back to the
+    /// profile environment variable for the `config_env_prefix` (if any, e.g.
+    /// `MY_APP_PROFILE` for a prefix of `MY_APP`).
+    fn config_profile(&self, command: &Self::Cmd) -> Option<String> {
+        command.config_profile().or_else(|| {
+            let prefix = self.config_env_prefix()?;
+            env::var(config::profile::env_var(&prefix))
+                .ok()
+                .filter(|profile| !profile.is_empty())
+        })
+    }
+
+    /// Configuration loader used to load (and reload) this application's
+    /// configuration for the given command.
+    ///
+    /// By default this deep-merges the command's `config_sources` (migrated
+    /// using `config_migrations`) along with the selected `config_profile`
+    /// (if `config_profiles` are enabled or one is selected), and then
+    /// overlays the environment variables with the `config_env_prefix` (if
+    /// any) followed by the command's `config_assignments`.
+    fn config_loader(&self, command: &Self::Cmd) -> Loader {
+        let mut loader = Loader::new();
+        loader
+            .sources(command.config_sources())
+            .migrations(self.config_migrations())
+            .profiles(self.config_profiles())
+            .profile(self.config_profile(command))
+            .assignments(command.config_assignments());
+
+        if let Some(prefix) = self.config_env_prefix() {
+            loader.env_prefix(prefix);
+        }
+
+        if let Some(mode) = self.config_unknown_keys() {
+            loader.unknown_keys(mode);
+        }
+
+        loader
+    }
+
+    /// Reload this application's configuration for the command it was
+    /// booted with (see `State::command`), notifying components via
+    /// `Component::on_config_reload`.
     ///
-    /// Returns an error if the configuration could not be loaded.
-    fn load_config(&mut self, path: &Path) -> Result<Self::Cfg, FrameworkError> {
-        let canonical_path = AbsPathBuf::canonicalize(path).map_err(|e| {
-            let path_error = PathError {
-                name: Some(path.into()),
-            };
-            FrameworkError::from(ConfigError.context(path_error))
-        })?;
-        Self::Cfg::load_toml_file(&canonical_path)
+    /// Like `after_config`, this is handled by the standard application
+    /// template (which owns the application's `CfgCell`) by calling
+    /// `CfgCell::reload`. The default implementation returns an error.
+    fn reload_config(&self) -> Result<(), FrameworkError> {
+        fail!(
+            ConfigError,
+            "{} does not support reloading config",
+            self.name()
+        );
     }
 
     /// Name of this application as a string.
@@ -156,21 +305,183 @@ pub trait Application: Default + Sized + 'static {
         trace::Config::default()
     }
 
+    /// Signals handled by this application (see the [`signal`][`crate::signal`]
+    /// module).
+    ///
+    /// By default these are `SIGINT` and `SIGTERM`, along with the signals
+    /// handled by components (see `Component::signals`). Return an empty
+    /// list to opt out of signal handling, e.g. to install handlers some
+    /// other way.
+    #[cfg(all(unix, feature = "signals"))]
+    fn signals(&self) -> Vec<Signal> {
+        let mut signals = Signal::SHUTDOWN.to_vec();
+
+        for signal in self.state().components().signals() {
+            if !signals.contains(&signal) {
+                signals.push(signal);
+            }
+        }
+
+        signals
+    }
+
+    /// Handle a signal received by this application, returning the kind of
+    /// shutdown to perform (if any).
+    ///
+    /// This is invoked on the signal handling thread, so it should return
+    /// promptly. By default `SIGINT` and `SIGTERM` request a graceful
+    /// shutdown, which is escalated to a forced one if another signal arrives
+    /// while it's in progress. Signals for which this returns `None` are
+    /// passed to components' `Component::on_signal` hooks, after reloading
+    /// the application's configuration with `Application::reload_config` in
+    /// the case of `SIGHUP`.
+    #[cfg(all(unix, feature = "signals"))]
+    fn handle_signal(&self, signal: Signal) -> Option<Shutdown> {
+        if Signal::SHUTDOWN.contains(&signal) {
+            Some(Shutdown::Graceful)
+        } else {
+            None
+        }
+    }
+
+    /// Directory crash reports are written to if the application panics.
+    ///
+    /// By default this is the `crash-reports` directory within the
+    /// application's root directory (see [`StandardPaths`]), which is
+    /// created if needed (only accessible by the current user on Unix).
+    /// Return `None` to disable crash reports; components' `before_shutdown`
+    /// hooks are still invoked with `Shutdown::Crash` before the application
+    /// exits with [`CRASH_EXIT_CODE`].
+    ///
+    /// This is also invoked before the application is initialized, to write
+    /// crash reports for panics during initialization.
+    fn crash_reports_dir(&self) -> Option<PathBuf> {
+        Some(
+            self.state()
+                .paths()
+                .root()
+                .as_path()
+                .join(path::CRASH_REPORTS_DIR),
+        )
+    }
+
+    /// Time limit for each component's `before_shutdown` callback during the
+    /// given kind of shutdown (or `None` for no limit).
+    ///
+    /// Components which exceed it are abandoned, and graceful shutdowns
+    /// escalate to forced ones. By default this is 30 seconds for graceful
+    /// shutdowns and 5 seconds otherwise.
+    fn shutdown_deadline(&self, shutdown: Shutdown) -> Option<Duration> {
+        match shutdown {
+            Shutdown::Graceful => Some(Duration::from_secs(30)),
+            Shutdown::Forced | Shutdown::Crash => Some(Duration::from_secs(5)),
+        }
+    }
+
+    /// Time to wait for threads spawned by the thread manager to exit after
+    /// their kill switches are thrown during a graceful shutdown.
+    ///
+    /// Threads which are still running afterwards are reported by name and
+    /// the application exits with a failure status. By default this is 10
+    /// seconds.
+    fn thread_join_timeout(&self) -> Duration {
+        Duration::from_secs(10)
+    }
+
     /// Shut down this application gracefully, exiting with success.
     fn shutdown(&self, shutdown: Shutdown) -> ! {
-        let components = self.state().components();
+        self.shutdown_with_exit_code(shutdown, 0)
+    }
+
+    /// Shut down this application, exiting with the given status code.
+    ///
+    /// This requests that all threads spawned by the thread manager
+    /// terminate, and then invokes `Component::before_shutdown` on every
+    /// component. Graceful shutdowns then join these threads, waiting at most
+    /// [`Application::thread_join_timeout`] for them to exit.
+    ///
+    /// Exits with a failure status if any component fails to shut down, or if
+    /// any thread panicked or didn't exit in time. A non-zero `exit_code`
+    /// (e.g. the exit code for the error a command returned) is kept in that
+    /// case, rather than being replaced by the generic failure status.
+    ///
+    /// Components which exceed their [`Application::shutdown_deadline`] may
+    /// still be running when the application exits, in which case the
+    /// component registry remains locked for reading until then.
+    ///
+    /// Only one shutdown runs at a time: if the application is already
+    /// shutting down, this escalates that shutdown to a forced one (unless
+    /// this one is graceful) and waits for it to exit the process. Graceful
+    /// shutdowns wait for the component registry to be unlocked unless
+    /// they're escalated, whereas forced ones exit without shutting
+    /// components down if it's locked for writing (e.g. by a config reload).
+    fn shutdown_with_exit_code(&self, shutdown: Shutdown, exit_code: i32) -> ! {
+        if !self.state().begin_shutdown() {
+            if shutdown != Shutdown::Graceful {
+                self.state().escalate_shutdown();
+            }
 
-        if let Err(e) = components.shutdown(self, shutdown) {
-            fatal_error(self, &e)
+            loop {
+                thread::park();
+            }
         }
 
-        process::exit(0);
+        self.state().threads().request_termination();
+
+        let components = loop {
+            if let Some(components) = self.state().try_components() {
+                break components;
+            }
+
+            if shutdown != Shutdown::Graceful || self.state().is_shutdown_escalated() {
+                warn!("component registry is locked: skipping component shutdown");
+                exit_after_shutdown(self, Shutdown::Forced, exit_code, Ok(()));
+            }
+
+            thread::sleep(ESCALATION_POLL_INTERVAL);
+        };
+
+        let result = components.shutdown_within_deadlines(self, shutdown, |result| {
+            exit_after_shutdown(self, shutdown, exit_code, result)
+        });
+
+        drop(components);
+        exit_after_shutdown(self, shutdown, exit_code, result)
+    }
+}
+
+/// Join the threads spawned by the thread manager after components have
+/// been shut down (if the shutdown is still graceful), and exit.
+fn exit_after_shutdown<A: Application>(
+    app: &A,
+    shutdown: Shutdown,
+    exit_code: i32,
+    components: Result<(), FrameworkError>,
+) -> ! {
+    let threads = if shutdown == Shutdown::Graceful && !app.state().is_shutdown_escalated() {
+        app.state()
+            .threads_mut()
+            .join_timeout(app.thread_join_timeout())
+    } else {
+        Ok(())
+    };
+
+    let errors = [components.err(), threads.err()];
+
+    for err in errors.iter().flatten() {
+        exit::print_fatal_error(app, err);
     }
+
+    if exit_code == 0 && errors.iter().any(Option::is_some) {
+        process::exit(1);
+    }
+
+    process::exit(exit_code)
 }
 
 /// Boot the given application, parsing subcommand and options from
 /// command-line arguments, and terminating when complete.
-pub fn boot<A: Application>(app_cell: &'static AppCell<A>) -> ! {
+pub fn boot<A: Application + Send + Sync>(app_cell: &'static AppCell<A>) -> ! {
     let args = env::args();
     A::run(app_cell, args);
     process::exit(0);
@@ -14,6 +14,12 @@ impl<T> AppCell<T> {
     }
 }
 
+impl<T> Default for AppCell<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<A> AppCell<A>
 where
     A: Application,
@@ -26,6 +32,19 @@ where
             panic!("can't reset Abscissa application state (yet)!");
         })
     }
+
+    /// Get the application, if it's been set.
+    pub(crate) fn get(&self) -> Option<&A> {
+        self.0.get()
+    }
+}
+
+/// Applications whose state is held in a static [`AppCell`] (e.g. the `APP`
+/// static of the standard application template), so reusable commands can
+/// access the booted application.
+pub trait StaticApp: Application {
+    /// Get the cell holding the application's state.
+    fn app_cell() -> &'static AppCell<Self>;
 }
 
 impl<A> Deref for AppCell<A>
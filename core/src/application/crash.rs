@@ -0,0 +1,278 @@
+//! Crash handling: a panic hook which shuts components down with
+//! `Shutdown::Crash` and writes a crash report.
+
+use super::{AppCell, Application};
+use crate::{command::Command, fs, shutdown::Shutdown, FrameworkError};
+use backtrace::Backtrace;
+use clap::CommandFactory;
+use once_cell::sync::OnceCell;
+use std::{
+    fmt::Write as _,
+    io::{self, Write as _},
+    panic,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+#[cfg(unix)]
+use {crate::fs::os::unix::fs::OpenOptionsExt, std::os::unix::fs::DirBuilderExt};
+
+/// Placeholder for redacted command-line arguments
+const REDACTED: &str = "<redacted>";
+
+/// Exit code used when the application crashes (`EX_SOFTWARE` from
+/// `sysexits.h`, i.e. an internal software error)
+pub const CRASH_EXIT_CODE: i32 = crate::error::exit_code::EX_SOFTWARE;
+
+/// Whether the application is already crashing
+static CRASHING: AtomicBool = AtomicBool::new(false);
+
+/// Crash handler of the application, run by the panic hook
+static HANDLER: OnceCell<Box<Handler>> = OnceCell::new();
+
+/// Number of times the panic hook has been installed: only the most recently
+/// installed hook runs the crash handler, and earlier ones it chains to (if
+/// any) only run the hooks they replaced
+static GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+/// Crash handler invoked with the panics of the application's main thread
+// `PanicInfo` was renamed `PanicHookInfo` in Rust 1.81 (above our MSRV)
+#[allow(deprecated)]
+type Handler = dyn Fn(&panic::PanicInfo<'_>) + Send + Sync;
+
+/// Install a panic hook which (after running the previously installed hook)
+/// writes a crash report, invokes `Component::before_shutdown` with
+/// `Shutdown::Crash`, and exits with [`CRASH_EXIT_CODE`].
+///
+/// Only panics on the calling thread (i.e. the one initializing the
+/// application and running its command) are handled this way. Panics on
+/// other threads are left to the previous hook, and surface as errors when
+/// those threads are joined.
+///
+/// Until the application is booted (i.e. stored in the given cell), crash
+/// reports are written to `boot_reports_dir`, and there are no components to
+/// shut down.
+pub(crate) fn install_hook<A: Application + Send + Sync>(
+    app_cell: &'static AppCell<A>,
+    boot_reports_dir: Option<PathBuf>,
+    args: Vec<String>,
+) {
+    let main_thread = thread::current().id();
+
+    let handler: Box<Handler> = Box::new(move |info| {
+        if thread::current().id() != main_thread {
+            return;
+        }
+
+        // Only handle the first panic, e.g. if a `Drop` impl panics while
+        // unwinding
+        if CRASHING.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let app = app_cell.get();
+
+        let reports_dir = match app {
+            Some(app) => app.crash_reports_dir(),
+            None => boot_reports_dir.clone(),
+        };
+
+        if let Some(dir) = reports_dir {
+            match write_report::<A>(&dir, &report(app, &args, info)) {
+                Ok(path) => eprintln!("crash report written to {}", path.display()),
+                Err(e) => eprintln!("error writing crash report: {}", e),
+            }
+        }
+
+        // Components aren't shut down until the application is booted
+        let app = match app {
+            Some(app) => app,
+            None => process::exit(CRASH_EXIT_CODE),
+        };
+
+        // The registry can't be locked if the panic occurred while it was
+        // locked for writing
+        match app.state().try_components() {
+            Some(components) => {
+                let result = components.shutdown_within_deadlines(app, Shutdown::Crash, |result| {
+                    exit_after_crash(result)
+                });
+
+                exit_after_crash(result)
+            }
+            None => {
+                eprintln!("component registry is locked: skipping crash shutdown");
+                process::exit(CRASH_EXIT_CODE);
+            }
+        }
+    });
+
+    if HANDLER.set(handler).is_ok() {
+        reinstall_hook();
+    }
+}
+
+/// Reinstall the panic hook installed by [`install_hook`] (if any), on top
+/// of the current one.
+///
+/// Panic hooks aren't chained by default: this is needed after installing
+/// another hook (e.g. `color-eyre`'s) so crashes are still handled.
+pub(crate) fn reinstall_hook() {
+    let handler = match HANDLER.get() {
+        Some(handler) => handler,
+        None => return,
+    };
+
+    let previous = panic::take_hook();
+    let generation = GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+
+    panic::set_hook(Box::new(move |info| {
+        previous(info);
+
+        if GENERATION.load(Ordering::SeqCst) == generation {
+            handler(info);
+        }
+    }));
+}
+
+/// Report the result of shutting components down after a crash, and exit.
+fn exit_after_crash(result: Result<(), FrameworkError>) -> ! {
+    if let Err(e) = result {
+        eprintln!("error shutting down components after crash: {}", e);
+    }
+
+    process::exit(CRASH_EXIT_CODE)
+}
+
+/// Generate a crash report for the given panic.
+// `PanicInfo` was renamed `PanicHookInfo` in Rust 1.81 (above our MSRV)
+#[allow(deprecated)]
+fn report<A: Application>(app: Option<&A>, args: &[String], info: &panic::PanicInfo<'_>) -> String {
+    let message = if let Some(message) = info.payload().downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = info.payload().downcast_ref::<String>() {
+        message.as_str()
+    } else {
+        "Box<dyn Any>"
+    };
+
+    let version = A::Cmd::command()
+        .get_version()
+        .map(str::to_owned)
+        .unwrap_or_else(|| "unknown".to_owned());
+
+    let mut report = String::new();
+
+    // Writing to a `String` can't fail
+    writeln!(report, "{} crash report", app_name(app)).unwrap();
+    writeln!(report).unwrap();
+    writeln!(report, "version: {}", version).unwrap();
+    writeln!(report, "time: {}", unix_time()).unwrap();
+    writeln!(report, "command line: {:?}", redact_args(args)).unwrap();
+    writeln!(
+        report,
+        "thread: {}",
+        thread::current().name().unwrap_or("<unnamed>")
+    )
+    .unwrap();
+    writeln!(report, "message: {}", message).unwrap();
+
+    if let Some(location) = info.location() {
+        writeln!(report, "location: {}", location).unwrap();
+    }
+
+    writeln!(report).unwrap();
+    writeln!(report, "components:").unwrap();
+
+    match app.map(|app| app.state().try_components()) {
+        Some(Some(components)) => {
+            for component in components.iter() {
+                writeln!(report, "- {} {}", component.id(), component.version()).unwrap();
+            }
+        }
+        Some(None) => writeln!(report, "<registry locked>").unwrap(),
+        None => writeln!(report, "<application not booted>").unwrap(),
+    }
+
+    writeln!(report).unwrap();
+    writeln!(report, "backtrace:").unwrap();
+    writeln!(report, "{:?}", Backtrace::new()).unwrap();
+
+    report
+}
+
+/// Write the given crash report into the given directory, returning the
+/// path of the report.
+///
+/// On Unix, the directory (if it's created) and the report are only
+/// accessible by the current user. Reports are never written through
+/// existing files (or symlinks).
+fn write_report<A: Application>(dir: &Path, report: &str) -> io::Result<PathBuf> {
+    let mut dir_builder = std::fs::DirBuilder::new();
+    dir_builder.recursive(true);
+
+    #[cfg(unix)]
+    dir_builder.mode(0o700);
+
+    dir_builder.create(dir).map_err(|e| {
+        io::Error::new(
+            e.kind(),
+            format!("failed to create directory `{}`: {}", dir.display(), e),
+        )
+    })?;
+
+    let path = dir.join(format!(
+        "{}-{}-{}.txt",
+        A::Cmd::name(),
+        unix_time(),
+        process::id()
+    ));
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+
+    #[cfg(unix)]
+    options.mode(0o600);
+
+    options.open(&path)?.write_all(report.as_bytes())?;
+    Ok(path)
+}
+
+/// Get the name of the application (which may not have been booted yet).
+fn app_name<A: Application>(app: Option<&A>) -> &'static str {
+    match app {
+        Some(app) => app.name(),
+        None => A::Cmd::name(),
+    }
+}
+
+/// Redact the values in the given command-line arguments, which may contain
+/// secrets (e.g. `--set` assignments), keeping the program and option names.
+fn redact_args(args: &[String]) -> Vec<String> {
+    args.iter()
+        .enumerate()
+        .map(|(i, arg)| {
+            if i == 0 || (arg.starts_with('-') && arg.len() == 2) {
+                arg.clone()
+            } else if arg.starts_with("--") {
+                match arg.split_once('=') {
+                    Some((name, _)) => format!("{}={}", name, REDACTED),
+                    None => arg.clone(),
+                }
+            } else {
+                REDACTED.to_owned()
+            }
+        })
+        .collect()
+}
+
+/// Get the current time in seconds since the Unix epoch.
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
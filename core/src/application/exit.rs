@@ -1,24 +1,33 @@
 //! Default exit handlers for Abscissa applications
 
-use super::{Application, Component};
+use super::Application;
+use crate::{config::Diagnostic, terminal};
 use std::{error::Error, process};
 
 /// Print a fatal error message and exit
 pub fn fatal_error(app: &impl Application, err: &dyn Error) -> ! {
-    status_err!("{} fatal error: {}", app.name(), err);
+    print_fatal_error(app, err);
     process::exit(1)
 }
 
-/// Exit because component startup ordering could not be determined.
-/// This is a barebones implementation using basic std facilities
-/// because it might be called before the terminal component has been
-/// started, and we can't use it to log errors about itself.
-pub(crate) fn bad_component_order<A>(a: &dyn Component<A>, b: &dyn Component<A>) -> !
-where
-    A: Application,
-{
-    eprintln!("*** error(abscissa): couldn't determine startup order for components:");
-    eprintln!(" - {:?}", a);
-    eprintln!(" - {:?}", b);
-    process::exit(1)
+/// Print a fatal error message (along with any config file diagnostic).
+pub(crate) fn print_fatal_error(app: &impl Application, err: &dyn Error) {
+    status_err!("{} fatal error: {}", app.name(), err);
+    print_diagnostic(err);
+}
+
+/// Print an annotated snippet of the offending config file for the first
+/// [`Diagnostic`] among the sources of the given error (if any).
+pub(crate) fn print_diagnostic(err: &dyn Error) {
+    let mut source = err.source();
+
+    while let Some(err) = source {
+        if let Some(diagnostic) = err.downcast_ref::<Diagnostic>() {
+            let mut stderr = terminal::stderr().lock();
+            diagnostic.write_snippet(&mut stderr).ok();
+            return;
+        }
+
+        source = err.source();
+    }
 }
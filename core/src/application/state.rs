@@ -1,28 +1,80 @@
 //! Application state managed by the framework.
 
-use crate::{application::Application, component, thread};
-use std::sync::RwLock;
+use crate::{application::Application, component, config::Provenance, thread};
+use once_cell::sync::OnceCell;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    RwLock,
+};
+use toml::value::Table;
 
 /// Error message to use for mutex error panics.
 const MUTEX_ERR_MSG: &str = "error acquiring mutex";
 
 /// Framework-managed application state
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct State<A: Application + 'static> {
+    /// Command the application was booted with.
+    command: OnceCell<A::Cmd>,
+
     /// Application components.
     components: RwLock<component::Registry<A>>,
 
+    /// Provenance of the application's configuration, along with the merged
+    /// table it was deserialized from (if any sources were loaded).
+    config_provenance: RwLock<(Provenance, Option<Table>)>,
+
+    /// Profile the application's configuration was loaded with.
+    config_profile: RwLock<Option<String>>,
+
     /// Application paths.
     paths: A::Paths,
 
     /// Thread manager.
     threads: RwLock<thread::Manager>,
+
+    /// Whether the application is shutting down.
+    shutting_down: AtomicBool,
+
+    /// Whether the shutdown in progress has been escalated to a forced one.
+    shutdown_escalated: AtomicBool,
+}
+
+impl<A> Default for State<A>
+where
+    A: Application + 'static,
+{
+    fn default() -> Self {
+        Self {
+            command: OnceCell::new(),
+            components: RwLock::default(),
+            config_provenance: RwLock::default(),
+            config_profile: RwLock::default(),
+            paths: A::Paths::default(),
+            threads: RwLock::default(),
+            shutting_down: AtomicBool::new(false),
+            shutdown_escalated: AtomicBool::new(false),
+        }
+    }
 }
 
 impl<A> State<A>
 where
     A: Application + 'static,
 {
+    /// Get the command the application was booted with by
+    /// `Application::run`, if it has been.
+    pub fn command(&self) -> Option<&A::Cmd> {
+        self.command.get()
+    }
+
+    /// Set the command the application was booted with.
+    pub(crate) fn set_command(&self, command: A::Cmd) {
+        self.command.set(command).unwrap_or_else(|_| {
+            panic!("can't reset Abscissa application command!");
+        })
+    }
+
     /// Obtain a read-only lock on the component registry.
     pub fn components(&self) -> component::registry::Reader<'_, A> {
         self.components.read().expect(MUTEX_ERR_MSG)
@@ -33,6 +85,36 @@ where
         self.components.write().expect(MUTEX_ERR_MSG)
     }
 
+    /// Try to obtain a read-only lock on the component registry without
+    /// blocking, e.g. while crashing.
+    pub(crate) fn try_components(&self) -> Option<component::registry::Reader<'_, A>> {
+        self.components.try_read().ok()
+    }
+
+    /// Get the provenance of the application's configuration as loaded,
+    /// along with the merged table it was deserialized from.
+    pub(crate) fn config_provenance(&self) -> (Provenance, Option<Table>) {
+        self.config_provenance.read().expect(MUTEX_ERR_MSG).clone()
+    }
+
+    /// Record the provenance of the application's configuration.
+    pub(crate) fn set_config_provenance(&self, provenance: Provenance, table: Option<Table>) {
+        *self.config_provenance.write().expect(MUTEX_ERR_MSG) = (provenance, table);
+    }
+
+    /// Get the [profile][`crate::config::profile`] the application's
+    /// configuration was loaded (or last reloaded) with, if any.
+    ///
+    /// This is set before `Application::after_config` is invoked.
+    pub fn config_profile(&self) -> Option<String> {
+        self.config_profile.read().expect(MUTEX_ERR_MSG).clone()
+    }
+
+    /// Record the profile the application's configuration was loaded with.
+    pub(crate) fn set_config_profile(&self, profile: Option<&str>) {
+        *self.config_profile.write().expect(MUTEX_ERR_MSG) = profile.map(str::to_owned);
+    }
+
     /// Borrow the application paths.
     pub fn paths(&self) -> &A::Paths {
         &self.paths
@@ -47,4 +129,26 @@ where
     pub fn threads_mut(&self) -> thread::manager::Writer<'_> {
         self.threads.write().expect(MUTEX_ERR_MSG)
     }
+
+    /// Mark the application as shutting down, returning `false` if it
+    /// already was.
+    pub(crate) fn begin_shutdown(&self) -> bool {
+        !self.shutting_down.swap(true, Ordering::SeqCst)
+    }
+
+    /// Is the application shutting down?
+    #[cfg(all(unix, feature = "signals"))]
+    pub(crate) fn is_shutting_down(&self) -> bool {
+        self.shutting_down.load(Ordering::SeqCst)
+    }
+
+    /// Escalate the shutdown in progress to a forced one.
+    pub(crate) fn escalate_shutdown(&self) {
+        self.shutdown_escalated.store(true, Ordering::SeqCst);
+    }
+
+    /// Has the shutdown in progress been escalated to a forced one?
+    pub(crate) fn is_shutdown_escalated(&self) -> bool {
+        self.shutdown_escalated.load(Ordering::SeqCst)
+    }
 }
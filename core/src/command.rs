@@ -3,15 +3,15 @@
 #[doc(hidden)]
 pub use abscissa_derive::Command;
 
-use crate::{runnable::Runnable, terminal};
+use crate::{runnable::TryRunnable, terminal};
 use clap::{FromArgMatches, Parser};
 use std::{env, fmt::Debug};
 use termcolor::ColorChoice;
 
 /// Subcommand of an application: derives or otherwise implements the `Options`
-/// trait, but also has a `run()` method which can be used to invoke the given
-/// (sub)command.
-pub trait Command: Debug + FromArgMatches + Runnable {
+/// trait, but also has a `run()` (or fallible `try_run()`) method which can be
+/// used to invoke the given (sub)command.
+pub trait Command: Debug + FromArgMatches + TryRunnable {
     /// Name of this program as a string
     fn name() -> &'static str;
 
@@ -7,12 +7,21 @@
 mod handle;
 mod id;
 pub mod registry;
-
-pub use self::{handle::Handle, id::Id, registry::Registry};
+mod requirement;
+
+pub use self::{
+    handle::{Handle, TypedHandle},
+    id::Id,
+    registry::Registry,
+    requirement::Requirement,
+};
 pub use abscissa_derive::Component;
 
 use crate::{application::Application, shutdown::Shutdown, FrameworkError, Version};
-use std::{any::Any, cmp::Ordering, fmt::Debug, slice::Iter};
+use std::{any::Any, fmt::Debug, slice::Iter};
+
+#[cfg(all(unix, feature = "signals"))]
+use crate::signal::Signal;
 
 /// Application components.
 ///
@@ -24,7 +33,8 @@ use std::{any::Any, cmp::Ordering, fmt::Debug, slice::Iter};
 /// During application initialization, callbacks are sent to all components
 /// upon events like application configuration being loaded. The
 /// `register_dependency` callback is called for each dependency returned
-/// by the `dependencies` method.
+/// by the `dependencies` method, and for each of the `optional_dependencies`
+/// which are registered.
 ///
 /// Additionally, they receive a callback prior to application shutdown.
 ///
@@ -41,6 +51,17 @@ use std::{any::Any, cmp::Ordering, fmt::Debug, slice::Iter};
 /// ```
 ///
 /// This will automatically implement the entire trait for you.
+///
+/// Dependencies are declared with `#[component(inject = "...")]` attributes
+/// naming a callback and a component ID, optionally preceded by `optional`
+/// and followed by a semver requirement on the dependency's version:
+///
+/// ```ignore
+/// #[derive(Component, Debug)]
+/// #[component(inject = "init_tokio(abscissa_tokio::TokioComponent >=0.7)")]
+/// #[component(inject = "init_metrics(optional my_app::MetricsComponent)")]
+/// pub struct MyComponent {}
+/// ```
 pub trait Component<A>: AsAny + Debug + Send + Sync
 where
     A: Application,
@@ -59,6 +80,16 @@ where
         Ok(())
     }
 
+    /// Lifecycle event called when application configuration is reloaded.
+    ///
+    /// Components receive both the previous and the newly loaded config, and
+    /// may reject the new config by returning an error, in which case the
+    /// reload is rolled back and components which already accepted it are
+    /// notified again with the arguments reversed.
+    fn on_config_reload(&mut self, old: &A::Cfg, new: &A::Cfg) -> Result<(), FrameworkError> {
+        Ok(())
+    }
+
     /// Names of the components this component depends on.
     ///
     /// After this app's `after_config` callback is fired, the
@@ -67,6 +98,21 @@ where
         [].iter()
     }
 
+    /// Names of the components this component uses if they're registered.
+    ///
+    /// Like `dependencies`, these are started before this component and
+    /// passed to `register_dependency`, but they're skipped if they aren't
+    /// registered (at the time this component is registered).
+    fn optional_dependencies(&self) -> Iter<'_, Id> {
+        [].iter()
+    }
+
+    /// Requirements on the versions of this component's dependencies
+    /// (including optional ones), checked when components are registered.
+    fn version_requirements(&self) -> Iter<'_, Requirement> {
+        [].iter()
+    }
+
     /// Register a dependency of this component (a.k.a. "dependency injection")
     fn register_dependency(
         &mut self,
@@ -76,6 +122,32 @@ where
         unimplemented!();
     }
 
+    /// Signals this component handles with `on_signal` (see the
+    /// [`signal`][`crate::signal`] module).
+    ///
+    /// The application handles these in addition to the signals which shut
+    /// it down.
+    #[cfg(all(unix, feature = "signals"))]
+    fn signals(&self) -> Vec<Signal> {
+        vec![]
+    }
+
+    /// Lifecycle event called when the application receives one of the
+    /// signals listed by `signals`, e.g. `SIGHUP` to reload configuration or
+    /// `SIGUSR1` to reopen log files.
+    ///
+    /// This runs on a thread managed by the application rather than within
+    /// the signal handler. Errors are logged and don't stop the application.
+    ///
+    /// The component registry is locked for reading while hooks run, so they
+    /// can't reload the application's configuration themselves. Instead the
+    /// application does so on `SIGHUP` before invoking them (notifying
+    /// components via `on_config_reload`).
+    #[cfg(all(unix, feature = "signals"))]
+    fn on_signal(&self, signal: Signal) -> Result<(), FrameworkError> {
+        Ok(())
+    }
+
     /// Perform any tasks which should occur before the app exits
     fn before_shutdown(&self, kind: Shutdown) -> Result<(), FrameworkError> {
         Ok(())
@@ -91,25 +163,6 @@ where
     }
 }
 
-impl<A> PartialOrd for Box<dyn Component<A>>
-where
-    A: Application,
-{
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        if other.dependencies().any(|dep| *dep == self.id()) {
-            if self.dependencies().any(|dep| *dep == other.id()) {
-                None
-            } else {
-                Some(Ordering::Greater)
-            }
-        } else if self.dependencies().any(|dep| *dep == other.id()) {
-            Some(Ordering::Less)
-        } else {
-            Some(Ordering::Equal)
-        }
-    }
-}
-
 /// Dynamic type helper trait
 // TODO(tarcieri): eliminate this trait or hide it from the public API
 pub trait AsAny: Any {
@@ -1,7 +1,7 @@
 //! Component handles: opaque references to registered components
 
 use super::{id::Id, registry::Index};
-use std::fmt;
+use std::{fmt, marker::PhantomData};
 
 /// Component handles are references to components which have been registered
 /// with a `component::Registry`.
@@ -9,6 +9,9 @@ use std::fmt;
 /// However, unlike normal Rust references, component handles are a "weak"
 /// reference which is not checked by the borrow checker. This allows for
 /// complex reference graphs which are otherwise inexpressible in Rust.
+///
+/// Handles to components which have been removed from the registry are
+/// stale: looking them up returns `None`.
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
 pub struct Handle {
     /// Component name
@@ -35,3 +38,64 @@ impl fmt::Debug for Handle {
         write!(f, "Handle({})", self.id().as_ref())
     }
 }
+
+/// Component handles which are statically typed, i.e. which refer to a
+/// component of type `C`.
+///
+/// Obtained with `Registry::get_typed_handle`, and looked up with
+/// `Registry::get_typed` (or `get_typed_mut`), which return a `&C`.
+pub struct TypedHandle<C> {
+    /// Untyped handle
+    handle: Handle,
+
+    /// Type of the component
+    component: PhantomData<fn() -> C>,
+}
+
+impl<C> TypedHandle<C> {
+    /// Create a new typed handle from an untyped one
+    pub(crate) fn new(handle: Handle) -> Self {
+        Self {
+            handle,
+            component: PhantomData,
+        }
+    }
+
+    /// Get the identifier of the component this handle points to
+    pub fn id(self) -> Id {
+        self.handle.id()
+    }
+
+    /// Get the untyped handle for this component
+    pub fn handle(self) -> Handle {
+        self.handle
+    }
+}
+
+impl<C> Clone for TypedHandle<C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<C> Copy for TypedHandle<C> {}
+
+impl<C> PartialEq for TypedHandle<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.handle == other.handle
+    }
+}
+
+impl<C> Eq for TypedHandle<C> {}
+
+impl<C> From<TypedHandle<C>> for Handle {
+    fn from(handle: TypedHandle<C>) -> Handle {
+        handle.handle
+    }
+}
+
+impl<C> fmt::Debug for TypedHandle<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TypedHandle({})", self.id().as_ref())
+    }
+}
@@ -1,14 +1,28 @@
 //! Abscissa's component registry
 
-use super::{handle::Handle, id::Id, Component};
+use super::{
+    handle::{Handle, TypedHandle},
+    id::Id,
+    Component,
+};
 use crate::{
-    application::{self, Application},
-    shutdown::Shutdown,
+    application::Application,
+    shutdown::{Shutdown, ESCALATION_POLL_INTERVAL},
     FrameworkError,
-    FrameworkErrorKind::ComponentError,
+    FrameworkErrorKind::{ComponentError, ConfigError},
     Map,
 };
-use std::{any::TypeId, borrow::Borrow, slice, sync};
+use std::{
+    any::TypeId,
+    convert::Infallible,
+    slice,
+    sync::{self, mpsc},
+    thread,
+    time::{Duration, Instant},
+};
+
+#[cfg(all(unix, feature = "signals"))]
+use crate::signal::Signal;
 
 /// Iterator over the components in the registry.
 pub type Iter<'a, A> = slice::Iter<'a, Box<dyn Component<A>>>;
@@ -29,19 +43,50 @@ type IdMap = Map<Id, Index>;
 type TypeMap = Map<TypeId, Index>;
 
 /// Index type providing efficient access to a particular component.
+///
+/// Indexes refer to slots in the registry's generational arena. Slots are
+/// reused after their components are removed, but with a new generation, so
+/// indexes of removed components are detectably invalid.
 #[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
-pub struct Index(usize);
+pub struct Index {
+    /// Slot in the arena
+    slot: usize,
+
+    /// Generation of the slot when the component was registered
+    generation: u64,
+}
+
+/// Slot in the registry's generational arena.
+#[derive(Debug, Default)]
+struct Slot {
+    /// Current generation, incremented when the component is removed
+    generation: u64,
+
+    /// Position of the component in the start order (if occupied)
+    position: Option<usize>,
+}
 
 /// The component registry provides a system for runtime registration of
 /// application components which can interact with each other dynamically.
 ///
 /// Components are sorted according to a dependency ordering, started
 /// in-order, and at application termination time, shut down in reverse order.
+/// Components registered (or removed) at runtime are started after (or
+/// removed from) the ones which are already registered.
 #[derive(Debug, Default)]
 pub struct Registry<A: Application + 'static> {
-    /// Generational arena of registered components
+    /// Registered components, in the order they're started
     components: Vec<Box<dyn Component<A>>>,
 
+    /// Indexes of the registered components (in the same order)
+    indexes: Vec<Index>,
+
+    /// Generational arena mapping indexes to positions in `components`
+    slots: Vec<Slot>,
+
+    /// Unoccupied slots, available for reuse
+    free_slots: Vec<usize>,
+
     /// Map of component identifiers to their indexes
     id_map: IdMap,
 
@@ -53,69 +98,182 @@ impl<A> Registry<A>
 where
     A: Application + 'static,
 {
-    /// Register components, determining their dependency order
+    /// Register components, determining their dependency order.
+    ///
+    /// Components are sorted topologically so that each one is started after
+    /// its dependencies, otherwise preserving the order they're given in.
+    /// Returns an error if the dependencies contain a cycle, if a component
+    /// depends on a component which isn't registered, or if a dependency's
+    /// version doesn't satisfy a component's `version_requirements`.
+    ///
+    /// Components registered after the application's configuration has been
+    /// loaded should use [`Registry::register_after_config`] instead.
     pub fn register<I>(&mut self, components: I) -> Result<(), FrameworkError>
     where
         I: IntoIterator<Item = Box<dyn Component<A>>>,
     {
-        // TODO(tarcieri): flexible runtime registration?
+        let components = sort_by_dependencies(components.into_iter().collect(), &self.id_map)?;
+        self.check_duplicates(&components)?;
+        self.check_versions(&components)?;
+
+        for component in components {
+            self.register_component(component)?;
+        }
+
+        Ok(())
+    }
+
+    /// Register components in a live registry, i.e. after the application's
+    /// configuration has been loaded (e.g. plugins enabled by the config).
+    ///
+    /// Components are registered as with [`Registry::register`], and may
+    /// depend on components which are already registered. The new components
+    /// then receive the `after_config` callback with the given config,
+    /// followed by their dependencies via `register_dependency`.
+    ///
+    /// If any of these callbacks fail, all of the new components are removed
+    /// again (without invoking `before_shutdown`), leaving the registry as it
+    /// was before.
+    ///
+    /// Components which are already registered don't receive the new
+    /// components via `register_dependency`, even if they list them among
+    /// their `optional_dependencies`: they were started first, so they
+    /// would be shut down after them.
+    pub fn register_after_config<I>(
+        &mut self,
+        components: I,
+        config: &A::Cfg,
+    ) -> Result<(), FrameworkError>
+    where
+        I: IntoIterator<Item = Box<dyn Component<A>>>,
+    {
+        let start = self.components.len();
+        let result = self
+            .register(components)
+            .and_then(|()| self.init_components(start, config));
+
+        if result.is_err() {
+            self.unregister_from(start);
+        }
+
+        result
+    }
+
+    /// Remove a registered component, returning it.
+    ///
+    /// The component's `before_shutdown` callback is invoked with
+    /// `Shutdown::Graceful` before it's removed. Components which other
+    /// registered components depend on can't be removed, and the component
+    /// remains registered if `before_shutdown` returns an error.
+    ///
+    /// Handles to the removed component become invalid: looking them up
+    /// returns `None`, even after other components are registered.
+    pub fn remove(&mut self, handle: Handle) -> Result<Box<dyn Component<A>>, FrameworkError> {
+        let position = self
+            .position(handle.index)
+            .ok_or_else(|| format_err!(ComponentError, "no such component: {}", handle.id()))?;
+
+        let id = self.components[position].id();
+
+        let dependents = self
+            .components
+            .iter()
+            .filter(|component| {
+                component
+                    .dependencies()
+                    .chain(component.optional_dependencies())
+                    .any(|dep| *dep == id)
+            })
+            .map(|component| component.id().to_string())
+            .collect::<Vec<_>>();
+
         ensure!(
-            self.components.is_empty(),
+            dependents.is_empty(),
             ComponentError,
-            "no support for registering additional components (yet)"
+            "can't remove {}: depended on by {}",
+            id,
+            dependents.join(", ")
         );
 
-        let mut components = components.into_iter().collect::<Vec<_>>();
+        self.components[position].before_shutdown(Shutdown::Graceful)?;
 
-        components.sort_by(|a, b| {
-            a.partial_cmp(b)
-                .unwrap_or_else(|| application::exit::bad_component_order(a.borrow(), b.borrow()))
-        });
+        let component = self.components.remove(position);
+        let index = self.indexes.remove(position);
+        self.id_map.remove(&id);
+        self.type_map.remove(&(*component).type_id());
 
-        for component in components {
-            self.register_component(component)?;
+        // Invalidate the removed component's handles, and update the
+        // positions of the components after it
+        self.free_slot(index);
+
+        for (position, index) in self.indexes.iter().enumerate().skip(position) {
+            self.slots[index.slot].position = Some(position);
         }
 
-        Ok(())
+        debug!("removed component: {}", id);
+        Ok(component)
     }
 
     /// Callback fired by application when configuration has been loaded
     pub fn after_config(&mut self, config: &A::Cfg) -> Result<(), FrameworkError> {
-        let mut component_indexes: Vec<(Index, Vec<Index>)> = vec![];
-
-        for (index, component) in self.components.iter_mut().enumerate() {
-            // Fire the `after_config` callback for each subcomponent.
-            //
-            // Note that these are fired for *all* components prior to subcomponent registration
-            component.after_config(config)?;
-
-            let mut dep_indexes = vec![];
+        self.init_components(0, config)
+    }
 
-            for id in component.dependencies() {
-                if let Some(index) = self.id_map.get(id) {
-                    dep_indexes.push(*index);
-                } else {
-                    fail!(ComponentError, "unregistered dependency ID: {}", id);
+    /// Callback fired by application when configuration has been reloaded.
+    ///
+    /// Notifies components of the new configuration in dependency order. If
+    /// any component rejects it, the components which were already notified
+    /// are rolled back (in reverse order) by notifying them of the old config.
+    pub fn reload_config(&mut self, old: &A::Cfg, new: &A::Cfg) -> Result<(), FrameworkError> {
+        for i in 0..self.components.len() {
+            let err = match self.components[i].on_config_reload(old, new) {
+                Ok(()) => continue,
+                Err(err) => err,
+            };
+
+            let id = self.components[i].id();
+
+            for component in self.components[..i].iter_mut().rev() {
+                if let Err(e) = component.on_config_reload(new, old) {
+                    warn!("error rolling back config for {}: {}", component.id(), e);
                 }
             }
 
-            component_indexes.push((Index(index), dep_indexes));
+            fail!(ConfigError, "{} rejected config reload: {}", id, err);
         }
 
-        // Fire the `register_dependency` callbacks for each component's dependencies
-        for (component_index, dep_indexes) in component_indexes {
-            for dep_index in dep_indexes {
-                if let (Some(component), Some(dep)) = self.get2_mut(component_index, dep_index) {
-                    let dep_handle = Handle::new(dep.id(), dep_index);
-                    component.register_dependency(dep_handle, dep.as_mut())?;
-                } else {
-                    // In theory we just looked all of these up and they should always be valid
-                    unreachable!();
-                }
+        Ok(())
+    }
+
+    /// Signals handled by any of the components (see `Component::signals`)
+    #[cfg(all(unix, feature = "signals"))]
+    pub fn signals(&self) -> Vec<Signal> {
+        let mut signals = vec![];
+
+        for signal in self.components.iter().flat_map(|c| c.signals()) {
+            if !signals.contains(&signal) {
+                signals.push(signal);
             }
         }
 
-        Ok(())
+        signals
+    }
+
+    /// Callback fired by application when it receives a signal.
+    ///
+    /// Notifies the components which handle the given signal, logging any
+    /// errors they return.
+    #[cfg(all(unix, feature = "signals"))]
+    pub fn on_signal(&self, signal: Signal) {
+        for component in self.components.iter() {
+            if !component.signals().contains(&signal) {
+                continue;
+            }
+
+            if let Err(e) = component.on_signal(signal) {
+                error!("{} failed to handle {}: {}", component.id(), signal, e);
+            }
+        }
     }
 
     /// Get the number of currently registered components
@@ -130,12 +288,14 @@ where
 
     /// Get a component reference by its handle
     pub fn get(&self, handle: Handle) -> Option<&dyn Component<A>> {
-        self.components.get(handle.index.0).map(AsRef::as_ref)
+        let position = self.position(handle.index)?;
+        Some(self.components[position].as_ref())
     }
 
     /// Get a mutable component reference by its handle
     pub fn get_mut(&mut self, handle: Handle) -> Option<&mut (dyn Component<A> + 'static)> {
-        self.components.get_mut(handle.index.0).map(AsMut::as_mut)
+        let position = self.position(handle.index)?;
+        Some(self.components[position].as_mut())
     }
 
     /// Get a component's handle by its ID
@@ -168,24 +328,174 @@ where
         self.components.iter_mut()
     }
 
-    /// Shutdown components (in the reverse order they were started)
+    /// Shutdown components (in the reverse order they were started).
+    ///
+    /// Every component is shut down even if some of them fail, in which case
+    /// the returned error lists all of the failures.
     pub fn shutdown(&self, app: &A, shutdown: Shutdown) -> Result<(), FrameworkError> {
+        self.shutdown_with(
+            app,
+            shutdown,
+            || false,
+            |component, kind, _| Some(component.before_shutdown(kind)),
+        )
+    }
+
+    /// Shutdown components as with [`Registry::shutdown`], enforcing the
+    /// application's `shutdown_deadline`.
+    ///
+    /// Components whose `before_shutdown` callback exceeds it (or is still
+    /// running when a graceful shutdown is escalated, e.g. by a second
+    /// signal) are abandoned, i.e. left running on another thread, and a
+    /// graceful shutdown escalates to a forced one for the remaining
+    /// components. As abandoned callbacks still borrow the registry, the
+    /// result is passed to `abandoned` in that case, which can't return
+    /// (e.g. it exits the process).
+    pub fn shutdown_within_deadlines<F>(
+        &self,
+        app: &A,
+        shutdown: Shutdown,
+        abandoned: F,
+    ) -> Result<(), FrameworkError>
+    where
+        F: FnOnce(Result<(), FrameworkError>) -> Infallible,
+    {
+        thread::scope(|scope| {
+            let mut abandoned_any = false;
+
+            let escalated = || app.state().is_shutdown_escalated();
+
+            let result =
+                self.shutdown_with(app, shutdown, escalated, |component, kind, deadline| {
+                    let result =
+                        before_shutdown_within(scope, component, kind, deadline, escalated);
+
+                    abandoned_any |= result.is_none();
+                    result
+                });
+
+            if abandoned_any {
+                match abandoned(result) {}
+            }
+
+            result
+        })
+    }
+
+    /// Shutdown components, invoking `before_shutdown` on each of them
+    /// (with the deadline for the current kind of shutdown) via the given
+    /// function, which returns `None` if the deadline was exceeded.
+    fn shutdown_with<'a, E, F>(
+        &'a self,
+        app: &A,
+        shutdown: Shutdown,
+        escalated: E,
+        mut before_shutdown: F,
+    ) -> Result<(), FrameworkError>
+    where
+        E: Fn() -> bool,
+        F: FnMut(
+            &'a dyn Component<A>,
+            Shutdown,
+            Option<Duration>,
+        ) -> Option<Result<(), FrameworkError>>,
+    {
+        let mut kind = shutdown;
+        let mut failures = vec![];
+
         for component in self.components.iter().rev() {
-            component.before_shutdown(shutdown)?;
+            if kind == Shutdown::Graceful && escalated() {
+                kind = Shutdown::Forced;
+            }
+
+            let id = component.id();
+            let deadline = app.shutdown_deadline(kind);
+            let started = Instant::now();
+
+            match before_shutdown(&**component, kind, deadline) {
+                Some(Ok(())) => {
+                    debug!("{} shut down ({:?}) in {:?}", id, kind, started.elapsed());
+                }
+                Some(Err(e)) => {
+                    debug!(
+                        "{} failed to shut down ({:?}) in {:?}",
+                        id,
+                        kind,
+                        started.elapsed()
+                    );
+                    failures.push(format!("{}: {}", id, e));
+                }
+                None if deadline.map_or(true, |deadline| started.elapsed() < deadline) => {
+                    warn!("{} abandoned as the shutdown was escalated", id);
+                    failures.push(format!("{}: abandoned as the shutdown was escalated", id));
+                }
+                None => {
+                    let deadline = deadline.expect("no deadline");
+                    warn!(
+                        "{} exceeded {:?} shutdown deadline of {:?}",
+                        id, kind, deadline
+                    );
+
+                    failures.push(format!(
+                        "{}: exceeded {:?} shutdown deadline of {:?}",
+                        id, kind, deadline
+                    ));
+
+                    if kind == Shutdown::Graceful {
+                        kind = Shutdown::Forced;
+                    }
+                }
+            }
         }
 
+        ensure!(
+            failures.is_empty(),
+            ComponentError,
+            "{} component(s) failed to shut down:\n- {}",
+            failures.len(),
+            failures.join("\n- ")
+        );
+
         Ok(())
     }
 
     /// Get a component reference by its type
     pub fn get_downcast_ref<C>(&self) -> Option<&C>
+    where
+        C: Component<A>,
+    {
+        let position = self.position(*self.type_map.get(&TypeId::of::<C>())?)?;
+        (*self.components[position]).as_any().downcast_ref()
+    }
+
+    /// Get a typed handle to the component of the given type
+    pub fn get_typed_handle<C>(&self) -> Option<TypedHandle<C>>
     where
         C: Component<A>,
     {
         let index = *self.type_map.get(&TypeId::of::<C>())?;
-        self.components
-            .get(index.0)
-            .and_then(|box_component| (*(*box_component)).as_any().downcast_ref())
+        let position = self.position(index)?;
+        let handle = Handle::new(self.components[position].id(), index);
+        Some(TypedHandle::new(handle))
+    }
+
+    /// Get a component reference by its typed handle
+    pub fn get_typed<C>(&self, handle: TypedHandle<C>) -> Option<&C>
+    where
+        C: Component<A>,
+    {
+        (*self.components[self.position(handle.handle().index)?])
+            .as_any()
+            .downcast_ref()
+    }
+
+    /// Get a mutable component reference by its typed handle
+    pub fn get_typed_mut<C>(&mut self, handle: TypedHandle<C>) -> Option<&mut C>
+    where
+        C: Component<A>,
+    {
+        let position = self.position(handle.handle().index)?;
+        (*self.components[position]).as_mut_any().downcast_mut()
     }
 
     /// Get a mutable component reference by its type
@@ -193,10 +503,8 @@ where
     where
         C: Component<A>,
     {
-        let index = *self.type_map.get(&TypeId::of::<C>())?;
-        self.components
-            .get_mut(index.0)
-            .and_then(|box_component| (*(*box_component)).as_mut_any().downcast_mut())
+        let position = self.position(*self.type_map.get(&TypeId::of::<C>())?)?;
+        (*self.components[position]).as_mut_any().downcast_mut()
     }
 
     /// Register an individual component.
@@ -220,8 +528,19 @@ where
             id
         );
 
-        let index = Index(self.components.len());
+        let slot = self.free_slots.pop().unwrap_or_else(|| {
+            self.slots.push(Slot::default());
+            self.slots.len() - 1
+        });
+
+        let index = Index {
+            slot,
+            generation: self.slots[slot].generation,
+        };
+
+        self.slots[slot].position = Some(self.components.len());
         self.components.push(component);
+        self.indexes.push(index);
 
         // Index component by ID and type
         assert!(self.id_map.insert(id, index).is_none());
@@ -231,22 +550,310 @@ where
         Ok(())
     }
 
-    /// Borrow two components mutably (i.e. borrow splitting)
+    /// Remove the components starting at the given position, e.g. after
+    /// failing to initialize them.
+    fn unregister_from(&mut self, start: usize) {
+        while self.components.len() > start {
+            let component = self.components.pop().unwrap();
+            let index = self.indexes.pop().unwrap();
+            self.id_map.remove(&component.id());
+            self.type_map.remove(&(*component).type_id());
+            self.free_slot(index);
+
+            debug!("unregistered component: {}", component.id());
+        }
+    }
+
+    /// Invalidate the handles for the given index, and make its slot
+    /// available for reuse.
+    fn free_slot(&mut self, index: Index) {
+        let slot = &mut self.slots[index.slot];
+        slot.generation += 1;
+        slot.position = None;
+        self.free_slots.push(index.slot);
+    }
+
+    /// Fire the `after_config` callbacks of the components starting at the
+    /// given position, followed by their `register_dependency` callbacks.
+    fn init_components(&mut self, start: usize, config: &A::Cfg) -> Result<(), FrameworkError> {
+        // Fire the `after_config` callback for each subcomponent.
+        //
+        // Note that these are fired for *all* components prior to subcomponent registration
+        for component in &mut self.components[start..] {
+            component.after_config(config)?;
+        }
+
+        let mut dependencies = vec![];
+
+        for position in start..self.components.len() {
+            let component = &self.components[position];
+
+            for id in component.dependencies() {
+                match self.id_map.get(id).and_then(|&index| self.position(index)) {
+                    Some(dep_position) => dependencies.push((position, dep_position)),
+                    None => fail!(ComponentError, "unregistered dependency ID: {}", id),
+                }
+            }
+
+            for id in component.optional_dependencies() {
+                if let Some(dep_position) = self.id_map.get(id).and_then(|&i| self.position(i)) {
+                    dependencies.push((position, dep_position));
+                }
+            }
+        }
+
+        // Fire the `register_dependency` callbacks for each component's dependencies
+        for (position, dep_position) in dependencies {
+            let dep_index = self.indexes[dep_position];
+
+            if let (Some(component), Some(dep)) = self.get2_mut(position, dep_position) {
+                let dep_handle = Handle::new(dep.id(), dep_index);
+                component.register_dependency(dep_handle, dep.as_mut())?;
+            } else {
+                // In theory we just looked all of these up and they should always be valid
+                unreachable!();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check that none of the given (new) components are registered already,
+    /// or given more than once.
+    fn check_duplicates(&self, components: &[Box<dyn Component<A>>]) -> Result<(), FrameworkError> {
+        for (i, component) in components.iter().enumerate() {
+            let id = component.id();
+            let type_id = (**component).type_id();
+
+            ensure!(
+                !self.id_map.contains_key(&id)
+                    && !self.type_map.contains_key(&type_id)
+                    && !components[..i]
+                        .iter()
+                        .any(|other| other.id() == id || (**other).type_id() == type_id),
+                ComponentError,
+                "duplicate component registration: {}",
+                id
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Check the version requirements of the given (new) and registered
+    /// components on each other.
+    fn check_versions(&self, components: &[Box<dyn Component<A>>]) -> Result<(), FrameworkError> {
+        let all = || self.components.iter().chain(components);
+
+        for component in all() {
+            for req in component.version_requirements() {
+                let dep = match all().find(|dep| dep.id() == req.id()) {
+                    Some(dep) => dep,
+                    None => continue,
+                };
+
+                let version = dep.version();
+
+                ensure!(
+                    req.matches(&version)?,
+                    ComponentError,
+                    "{} requires {} {} (found {})",
+                    component.id(),
+                    req.id(),
+                    req.version(),
+                    version
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Find the position of the component with the given index (if it's
+    /// still registered)
+    fn position(&self, index: Index) -> Option<usize> {
+        let slot = self.slots.get(index.slot)?;
+
+        if slot.generation == index.generation {
+            slot.position
+        } else {
+            None
+        }
+    }
+
+    /// Borrow the components at two positions mutably (i.e. borrow splitting)
     #[allow(clippy::type_complexity)]
     fn get2_mut(
         &mut self,
-        a: Index,
-        b: Index,
+        a: usize,
+        b: usize,
     ) -> (
         Option<&mut Box<dyn Component<A>>>,
         Option<&mut Box<dyn Component<A>>>,
     ) {
-        let (a_slice, b_slice) = if b.0 <= self.components.len() {
-            self.components.split_at_mut(b.0)
+        if a < b && b < self.components.len() {
+            let (a_slice, b_slice) = self.components.split_at_mut(b);
+            (a_slice.get_mut(a), b_slice.first_mut())
+        } else if b < a && a < self.components.len() {
+            let (b_slice, a_slice) = self.components.split_at_mut(a);
+            (a_slice.first_mut(), b_slice.get_mut(b))
         } else {
-            (self.components.as_mut(), Default::default())
-        };
+            (None, None)
+        }
+    }
+}
 
-        (a_slice.get_mut(a.0), b_slice.first_mut())
+/// Sort the given components topologically, so that each one comes after
+/// its dependencies, breaking ties by the order they're given in.
+///
+/// Dependencies must either be among the given components or already
+/// registered (i.e. in `registered`). Optional dependencies are ordered
+/// the same way if they're among the given components.
+fn sort_by_dependencies<A>(
+    components: Vec<Box<dyn Component<A>>>,
+    registered: &IdMap,
+) -> Result<Vec<Box<dyn Component<A>>>, FrameworkError>
+where
+    A: Application,
+{
+    let ids = components.iter().map(|c| c.id()).collect::<Vec<_>>();
+
+    // Dependencies of each component which are among the given components
+    let mut dependencies = Vec::with_capacity(components.len());
+
+    for component in &components {
+        let mut indexes = vec![];
+
+        for dep in component.dependencies() {
+            match ids.iter().position(|id| id == dep) {
+                Some(index) => indexes.push(index),
+                None => ensure!(
+                    registered.contains_key(dep),
+                    ComponentError,
+                    "{} depends on unregistered component: {}",
+                    component.id(),
+                    dep
+                ),
+            }
+        }
+
+        for dep in component.optional_dependencies() {
+            if let Some(index) = ids.iter().position(|id| id == dep) {
+                indexes.push(index);
+            }
+        }
+
+        dependencies.push(indexes);
     }
+
+    let mut order = Vec::with_capacity(components.len());
+    let mut sorted = vec![false; components.len()];
+
+    while order.len() < components.len() {
+        // First unsorted component whose dependencies are all sorted
+        let next = (0..components.len())
+            .find(|&i| !sorted[i] && dependencies[i].iter().all(|&dep| sorted[dep]));
+
+        match next {
+            Some(index) => {
+                sorted[index] = true;
+                order.push(index);
+            }
+            None => fail!(
+                ComponentError,
+                "dependency cycle: {}",
+                find_cycle(&ids, &dependencies, &sorted)
+            ),
+        }
+    }
+
+    let mut components = components.into_iter().map(Some).collect::<Vec<_>>();
+
+    Ok(order
+        .into_iter()
+        .map(|index| components[index].take().expect("component sorted twice"))
+        .collect())
+}
+
+/// Find a dependency cycle among the unsorted components, returning its
+/// path (e.g. `a -> b -> a`).
+fn find_cycle(ids: &[Id], dependencies: &[Vec<usize>], sorted: &[bool]) -> String {
+    // Every unsorted component has an unsorted dependency, so following them
+    // from any unsorted component must eventually revisit one
+    let mut path = vec![sorted.iter().position(|s| !s).expect("no cycle")];
+
+    loop {
+        let last = path[path.len() - 1];
+        let next = dependencies[last]
+            .iter()
+            .copied()
+            .find(|&dep| !sorted[dep])
+            .expect("no unsorted dependency");
+
+        if let Some(start) = path.iter().position(|&index| index == next) {
+            return path[start..]
+                .iter()
+                .chain(Some(&next))
+                .map(|&index| ids[index].to_string())
+                .collect::<Vec<_>>()
+                .join(" -> ");
+        }
+
+        path.push(next);
+    }
+}
+
+/// Invoke `before_shutdown` on the given component on another thread,
+/// returning `None` if it doesn't complete within the given deadline (if
+/// any), or before a graceful shutdown is escalated.
+fn before_shutdown_within<'scope, A, E>(
+    scope: &'scope thread::Scope<'scope, '_>,
+    component: &'scope dyn Component<A>,
+    kind: Shutdown,
+    deadline: Option<Duration>,
+    escalated: E,
+) -> Option<Result<(), FrameworkError>>
+where
+    A: Application,
+    E: Fn() -> bool,
+{
+    let (sender, receiver) = mpsc::channel();
+
+    let spawned = thread::Builder::new()
+        .name("abscissa-component-shutdown".to_owned())
+        .spawn_scoped(scope, move || {
+            sender.send(component.before_shutdown(kind)).ok();
+        });
+
+    let handle = match spawned {
+        Ok(handle) => handle,
+        Err(e) => return Some(Err(e.into())),
+    };
+
+    let started = Instant::now();
+
+    let result = loop {
+        let timeout = match deadline {
+            Some(deadline) => deadline
+                .checked_sub(started.elapsed())?
+                .min(ESCALATION_POLL_INTERVAL),
+            None => ESCALATION_POLL_INTERVAL,
+        };
+
+        match receiver.recv_timeout(timeout) {
+            Ok(result) => break result,
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                if kind == Shutdown::Graceful && escalated() {
+                    return None;
+                }
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                break Err(format_err!(ComponentError, "panicked during shutdown").into());
+            }
+        }
+    };
+
+    // Join the thread so the scope doesn't propagate its panic (if any)
+    handle.join().ok();
+    Some(result)
 }
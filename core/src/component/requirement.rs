@@ -0,0 +1,68 @@
+//! Version requirements on component dependencies
+
+use super::id::Id;
+use crate::{FrameworkError, FrameworkErrorKind::ComponentError, Version};
+use semver::VersionReq;
+
+/// Requirement on the version of a component's dependency, e.g. that
+/// `abscissa_tokio::TokioComponent` is `>=0.7`.
+///
+/// Requirements use Cargo's semver syntax, and are checked against the
+/// dependency's `Component::version` when components are registered.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Requirement {
+    /// Identifier of the dependency
+    id: Id,
+
+    /// Semver requirement on its version
+    version: &'static str,
+}
+
+impl Requirement {
+    /// Create a new requirement that the given component's version matches
+    /// the given semver requirement.
+    pub const fn new(id: Id, version: &'static str) -> Self {
+        Self { id, version }
+    }
+
+    /// Get the identifier of the component this requirement applies to
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    /// Get the semver requirement on the component's version
+    pub fn version(&self) -> &'static str {
+        self.version
+    }
+
+    /// Does the given version satisfy this requirement?
+    pub fn matches(&self, version: &Version) -> Result<bool, FrameworkError> {
+        let req = VersionReq::parse(self.version).map_err(|e| {
+            format_err!(
+                ComponentError,
+                "invalid version requirement for {}: {}: {}",
+                self.id,
+                self.version,
+                e
+            )
+        })?;
+
+        Ok(req.matches(version))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches() {
+        let req = Requirement::new(Id::new("example::Component"), ">=0.7, <0.9");
+        assert!(req.matches(&Version::new(0, 7, 1)).unwrap());
+        assert!(!req.matches(&Version::new(0, 6, 0)).unwrap());
+        assert!(!req.matches(&Version::new(0, 9, 0)).unwrap());
+
+        let invalid = Requirement::new(Id::new("example::Component"), "not a version");
+        assert!(invalid.matches(&Version::new(0, 7, 1)).is_err());
+    }
+}
@@ -1,10 +1,38 @@
-//! Support for managing global configuration, as well as loading it from TOML.
+//! Support for managing global configuration, as well as loading it from TOML
+//! (or, with the corresponding cargo features, JSON, YAML, or RON).
 
+pub mod assignment;
 mod cell;
+#[cfg(feature = "application")]
+pub mod commands;
 mod configurable;
+mod diagnostic;
+pub mod env;
+mod format;
+mod interpolate;
+mod loader;
+pub mod migration;
 mod overrides;
+pub mod profile;
+pub mod provenance;
+#[cfg(feature = "schema")]
+pub mod schema;
+mod source;
+pub mod unknown_keys;
 
-pub use self::{cell::CfgCell, configurable::Configurable, overrides::Override};
+pub use self::{
+    assignment::Assignment,
+    cell::CfgCell,
+    configurable::Configurable,
+    diagnostic::Diagnostic,
+    format::Format,
+    loader::{merge, Loader},
+    migration::Migrations,
+    overrides::Override,
+    provenance::{Origin, Provenance},
+    source::Source,
+    unknown_keys::UnknownKeys,
+};
 
 use crate::{
     fs::File,
@@ -12,7 +40,7 @@ use crate::{
     FrameworkError,
     FrameworkErrorKind::{ConfigError, IoError, PathError},
 };
-use serde::de::DeserializeOwned;
+use serde::{de::DeserializeOwned, Serializer};
 use std::{fmt::Debug, io::Read};
 
 /// Configuration reader.
@@ -27,6 +55,15 @@ pub trait Config: Debug + Default + DeserializeOwned {
     /// Load the global configuration from the TOML file at the given path.
     /// If an error occurs reading or parsing the file, print it out and exit.
     fn load_toml_file(path: impl AsRef<AbsPath>) -> Result<Self, FrameworkError>;
+
+    /// Load the configuration from the given TOML string, expanding
+    /// `${VAR}`, `${VAR:-default}` and `${file:/path}` references inside
+    /// string values (with `$$` as an escape for `$`).
+    fn load_toml_interpolated(toml_string: impl AsRef<str>) -> Result<Self, FrameworkError>;
+
+    /// Load the configuration from the file at the given path, selecting the
+    /// [`Format`] based on its extension.
+    fn load_file(path: impl AsRef<AbsPath>) -> Result<Self, FrameworkError>;
 }
 
 impl<C> Config for C
@@ -34,21 +71,131 @@ where
     C: Debug + Default + DeserializeOwned,
 {
     fn load_toml(toml_string: impl AsRef<str>) -> Result<Self, FrameworkError> {
-        Ok(toml::from_str(toml_string.as_ref())?)
+        Format::Toml.parse(toml_string.as_ref(), None)
     }
 
     fn load_toml_file(path: impl AsRef<AbsPath>) -> Result<Self, FrameworkError> {
-        let mut file = File::open(path.as_ref().as_path()).map_err(|e| {
-            let io_error = IoError.context(e);
-            let path_error = PathError {
-                name: Some(path.as_ref().as_path().into()),
+        let path = path.as_ref();
+        Format::Toml.parse(&read_file(path)?, Some(path.as_path()))
+    }
+
+    fn load_toml_interpolated(toml_string: impl AsRef<str>) -> Result<Self, FrameworkError> {
+        let text = toml_string.as_ref();
+        let mut table = Format::Toml.parse(text, None)?;
+
+        interpolate::interpolate(&mut table)
+            .map_err(|e| ConfigError.context(e.to_diagnostic(Some(text))))?;
+
+        serde_path_to_error::deserialize(toml::Value::Table(table)).map_err(|e| {
+            let key_path = loader::map_keys(e.path());
+            let mut message = e.inner().to_string();
+            diagnostic::strip_key(&mut message);
+
+            let mut diagnostic = Diagnostic::new(message);
+
+            if !key_path.is_empty() {
+                diagnostic = diagnostic.with_key(e.path());
+
+                if let Some((line, column)) = diagnostic::locate(text, &key_path) {
+                    diagnostic = diagnostic.with_location(text, line, column);
+                }
             }
-            .context(io_error);
-            ConfigError.context(path_error)
-        })?;
 
-        let mut toml_string = String::new();
-        file.read_to_string(&mut toml_string)?;
-        Self::load_toml(toml_string)
+            ConfigError.context(diagnostic).into()
+        })
+    }
+
+    fn load_file(path: impl AsRef<AbsPath>) -> Result<Self, FrameworkError> {
+        let path = path.as_ref();
+        Format::from_path(path.as_path())?.parse(&read_file(path)?, Some(path.as_path()))
+    }
+}
+
+/// Placeholder which replaces redacted values when serializing
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Serialize a value (e.g. a [`Secret`][`crate::Secret`]) as [`REDACTED`].
+///
+/// Use this with `#[serde(serialize_with = "abscissa_core::config::redact")]`
+/// on fields which shouldn't be displayed by e.g. `config show`.
+pub fn redact<T, S>(_value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(REDACTED)
+}
+
+/// Print a notice about the configuration, e.g. that it's deprecated, and
+/// log it as a warning (with the `trace` feature).
+pub(crate) fn notice(message: String) {
+    #[cfg(feature = "trace")]
+    crate::tracing::warn!("{}", message);
+
+    if crate::terminal::is_initialized() {
+        status_warn!(message);
+    } else {
+        eprintln!("warning: {}", message);
+    }
+}
+
+/// Read the contents of the config file at the given path.
+pub(crate) fn read_file(path: &AbsPath) -> Result<String, FrameworkError> {
+    let mut file = File::open(path.as_path()).map_err(|e| {
+        let io_error = IoError.context(e);
+        let path_error = PathError {
+            name: Some(path.as_path().into()),
+        }
+        .context(io_error);
+        ConfigError.context(path_error)
+    })?;
+
+    let mut contents = String::new();
+    file.read_to_string(&mut contents)?;
+    Ok(contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Secret;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize)]
+    struct ExampleConfig {
+        user: String,
+        #[serde(serialize_with = "redact")]
+        password: Secret<String>,
+    }
+
+    #[test]
+    fn redact_secret() {
+        let config = ExampleConfig {
+            user: "example".to_owned(),
+            password: Secret::new("hunter2".to_owned()),
+        };
+
+        let toml = Format::Toml.serialize(&config).unwrap();
+        assert_eq!(toml, "user = 'example'\npassword = '[REDACTED]'\n");
+    }
+
+    #[derive(Debug, Default, Deserialize)]
+    struct DatabaseConfig {
+        url: String,
+    }
+
+    #[test]
+    fn interpolated_toml() {
+        std::env::set_var("ABSCISSA_DB_HOST", "db.example.com");
+
+        let config = DatabaseConfig::load_toml_interpolated(
+            "url = \"postgres://${ABSCISSA_DB_HOST}:${ABSCISSA_DB_PORT:-5432}/$$db\"",
+        )
+        .unwrap();
+        assert_eq!(config.url, "postgres://db.example.com:5432/$db");
+
+        let err =
+            DatabaseConfig::load_toml_interpolated("url = \"${ABSCISSA_DB_UNSET}\"").unwrap_err();
+        assert_eq!(*err.kind(), ConfigError);
+        assert!(err.to_string().contains("for key `url`"));
     }
 }
@@ -0,0 +1,155 @@
+//! Assignments of configuration values from the command line, e.g.
+//! `--set server.port=8080`.
+//!
+//! The right-hand side of an assignment is parsed as a TOML value (e.g.
+//! `42`, `true`, `"text"`, `[1, 2]`). Values which aren't valid TOML are
+//! treated as strings, so `--set hello.recipient=world` works unquoted.
+//!
+//! Assignments are applied to the merged configuration table after all
+//! other sources (including environment variables), before it's
+//! deserialized into the application's configuration type.
+
+use super::env;
+use crate::{FrameworkError, FrameworkErrorKind::ConfigError};
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+use toml::value::{Table, Value};
+
+/// Assignment of a value to a dotted key path, e.g. `server.port=8080`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Assignment {
+    /// Key path to assign
+    key_path: Vec<String>,
+
+    /// Value to assign
+    value: Value,
+
+    /// Original text of the assignment
+    text: String,
+}
+
+impl Assignment {
+    /// Get the key path this assignment sets.
+    pub fn key_path(&self) -> &[String] {
+        &self.key_path
+    }
+
+    /// Get the value this assignment sets.
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+
+    /// Set this assignment's value in the given table, creating any missing
+    /// parent tables.
+    pub(crate) fn apply(&self, table: &mut Table) -> Result<(), FrameworkError> {
+        let (key, parents) = self.key_path.split_last().expect("empty key path");
+        let mut table = table;
+
+        for segment in parents {
+            let value = table
+                .entry(segment.clone())
+                .or_insert_with(|| Value::Table(Table::new()));
+
+            table = match value {
+                Value::Table(t) => t,
+                _ => fail!(ConfigError, "--set {}: `{}` is not a table", self, segment),
+            };
+        }
+
+        table.insert(key.clone(), self.value.clone());
+        Ok(())
+    }
+}
+
+impl Display for Assignment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+impl FromStr for Assignment {
+    type Err = FrameworkError;
+
+    fn from_str(s: &str) -> Result<Self, FrameworkError> {
+        let (key, value) = match s.find('=') {
+            Some(pos) => (&s[..pos], &s[(pos + 1)..]),
+            None => fail!(ConfigError, "expected `KEY=VALUE`, got {:?}", s),
+        };
+
+        let key_path = key.trim().split('.').map(str::to_owned).collect::<Vec<_>>();
+
+        ensure!(
+            key_path.iter().all(|segment| !segment.is_empty()),
+            ConfigError,
+            "malformed key path: {:?}",
+            key
+        );
+
+        Ok(Self {
+            key_path,
+            value: env::infer(value),
+            text: s.to_owned(),
+        })
+    }
+}
+
+/// Command-line arguments for assigning configuration values.
+///
+/// Add them to the application's entry point with `#[command(flatten)]`.
+#[cfg(feature = "options")]
+#[derive(clap::Args, Clone, Debug, Default)]
+pub struct SetArgs {
+    /// Override a config value, e.g. `--set server.port=8080` (may be repeated)
+    #[arg(long = "set", value_name = "KEY=VALUE", global = true)]
+    pub assignments: Vec<Assignment>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let assignment: Assignment = "server.port=8080".parse().unwrap();
+        assert_eq!(assignment.key_path(), ["server", "port"]);
+        assert_eq!(assignment.value(), &Value::Integer(8080));
+
+        let assignment: Assignment = "peers=[\"a\", \"b=c\"]".parse().unwrap();
+        assert_eq!(
+            assignment.value(),
+            &Value::Array(vec!["a".into(), "b=c".into()])
+        );
+
+        let assignment: Assignment = "hello.recipient=world".parse().unwrap();
+        assert_eq!(assignment.value(), &Value::String("world".to_owned()));
+
+        assert!("server.port".parse::<Assignment>().is_err());
+        assert!("server..port=1".parse::<Assignment>().is_err());
+    }
+
+    #[test]
+    fn apply() {
+        let mut table: Table = toml::from_str("[server]\nhost = \"localhost\"").unwrap();
+
+        for text in &["server.port=8080", "tls.enabled=true"] {
+            text.parse::<Assignment>()
+                .unwrap()
+                .apply(&mut table)
+                .unwrap();
+        }
+
+        let expected: Table =
+            toml::from_str("[server]\nhost = \"localhost\"\nport = 8080\n[tls]\nenabled = true")
+                .unwrap();
+        assert_eq!(table, expected);
+
+        let err = "server.host.name=x"
+            .parse::<Assignment>()
+            .unwrap()
+            .apply(&mut table)
+            .unwrap_err();
+        assert_eq!(*err.kind(), ConfigError);
+    }
+}
@@ -1,6 +1,10 @@
 //! Configuration cell: holder of application configuration.
 
-use super::{Config, Reader};
+use super::{Config, Configurable, Loader, Reader};
+use crate::{
+    application::{Application, State},
+    FrameworkError,
+};
 use arc_swap::ArcSwapOption;
 use std::sync::Arc;
 
@@ -17,15 +21,52 @@ where
 {
     /// Set the application configuration to the given value.
     ///
-    /// This can only be performed once without causing a crash.
+    /// This can only be performed once without causing a crash. Use
+    /// [`CfgCell::reload`] to replace a configuration which is already set.
     pub fn set_once(&self, config: C) {
         let old_config = self.inner.swap(Some(Arc::new(config)));
 
         if old_config.is_some() {
-            panic!("can't reload Abscissa application config (yet)!");
+            panic!("Abscissa application config already set (use `CfgCell::reload`)!");
         }
     }
 
+    /// Reload the application configuration.
+    ///
+    /// Loads the configuration using the given loader (e.g. the one returned
+    /// by `Application::config_loader`), runs `Configurable::process_config`,
+    /// and notifies every component in the application's registry in
+    /// dependency order via `Component::on_config_reload`.
+    ///
+    /// The new configuration is only swapped in (and its provenance recorded
+    /// in the application's state) once all components have accepted it. If
+    /// any component rejects it, the old configuration is kept and a
+    /// `ConfigError` is returned.
+    pub fn reload<A>(
+        &self,
+        loader: &Loader,
+        command: &A::Cmd,
+        state: &State<A>,
+    ) -> Result<Reader<C>, FrameworkError>
+    where
+        A: Application<Cfg = C>,
+    {
+        let (config, provenance, table) = loader.load_with_table()?;
+
+        let new_config = Arc::new(command.process_config(config)?);
+        let old_config = self.read();
+
+        state
+            .components_mut()
+            .reload_config(&old_config, &new_config)?;
+
+        self.inner.store(Some(Arc::clone(&new_config)));
+        state.set_config_provenance(provenance, table);
+        state.set_config_profile(loader.selected_profile());
+
+        Ok(new_config)
+    }
+
     /// Read the current configuration.
     #[allow(clippy::redundant_closure)]
     pub fn read(&self) -> Reader<C> {
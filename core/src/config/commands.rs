@@ -0,0 +1,485 @@
+//! Reusable `config` subcommands.
+//!
+//! Mount them in the application's command enum with a single variant:
+//!
+//! ```ignore
+//! #[derive(clap::Parser, Command, Debug, TryRunnable)]
+//! pub enum MyAppCmd {
+//!     /// Inspect the application's configuration
+//!     #[command(subcommand)]
+//!     Config(abscissa_core::config::commands::ConfigCmd<MyApp>),
+//!
+//!     // ...
+//! }
+//! ```
+//!
+//! With the `schema` feature, this includes `config schema`, which prints the
+//! JSON Schema of the configuration.
+//!
+//! The application must implement [`StaticApp`], as `config show` and
+//! `config explain` inspect the configuration it was booted with.
+//!
+//! The configuration type must implement `Serialize` (and `JsonSchema` with
+//! the `schema` feature). [`Secret`][`crate::Secret`] fields should be
+//! annotated with `#[serde(serialize_with = "abscissa_core::config::redact")]`
+//! so their values are redacted in the output.
+
+#[cfg(feature = "schema")]
+use super::schema::{self, JsonSchema};
+use super::{provenance, read_file, Format, Source};
+use crate::{
+    application::{exit, StaticApp},
+    error::ExitCode,
+    path::AbsPathBuf,
+    Config, FrameworkError,
+    FrameworkErrorKind::ConfigError,
+    Runnable,
+};
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+use std::{marker::PhantomData, path::PathBuf, process};
+use toml::Value;
+
+/// Configuration types which can be used with the `config` subcommands.
+#[cfg(not(feature = "schema"))]
+pub trait CommandConfig: Config + Serialize {}
+
+#[cfg(not(feature = "schema"))]
+impl<C> CommandConfig for C where C: Config + Serialize {}
+
+/// Configuration types which can be used with the `config` subcommands
+/// (including `config schema`).
+#[cfg(feature = "schema")]
+pub trait CommandConfig: Config + Serialize + JsonSchema {}
+
+#[cfg(feature = "schema")]
+impl<C> CommandConfig for C where C: Config + Serialize + JsonSchema {}
+
+/// `config` subcommands for the application `A`.
+#[derive(Debug, Subcommand)]
+pub enum ConfigCmd<A>
+where
+    A: StaticApp,
+    A::Cfg: CommandConfig,
+{
+    /// Print the default configuration as TOML
+    Default(DefaultCmd<A::Cfg>),
+
+    /// Print the effective configuration as TOML
+    Show(ShowCmd<A>),
+
+    /// Check that a configuration file is valid
+    Check(CheckCmd<A>),
+
+    /// Explain where the value of a configuration key came from
+    Explain(ExplainCmd<A>),
+
+    /// Migrate a configuration file to the latest version in place
+    Migrate(MigrateCmd<A>),
+
+    /// Print the JSON Schema for the configuration
+    #[cfg(feature = "schema")]
+    Schema(SchemaCmd<A::Cfg>),
+}
+
+impl<A> Runnable for ConfigCmd<A>
+where
+    A: StaticApp,
+    A::Cfg: CommandConfig,
+{
+    fn run(&self) {
+        match self {
+            ConfigCmd::Default(cmd) => cmd.run(),
+            ConfigCmd::Show(cmd) => cmd.run(),
+            ConfigCmd::Check(cmd) => cmd.run(),
+            ConfigCmd::Explain(cmd) => cmd.run(),
+            ConfigCmd::Migrate(cmd) => cmd.run(),
+            #[cfg(feature = "schema")]
+            ConfigCmd::Schema(cmd) => cmd.run(),
+        }
+    }
+}
+
+/// `config default` subcommand: print the default configuration.
+#[derive(Debug, Parser)]
+pub struct DefaultCmd<C>
+where
+    C: CommandConfig + 'static,
+{
+    /// Configuration type
+    #[arg(skip)]
+    config: PhantomData<C>,
+}
+
+impl<C> Runnable for DefaultCmd<C>
+where
+    C: CommandConfig + 'static,
+{
+    fn run(&self) {
+        print_config(&C::default());
+    }
+}
+
+/// `config show` subcommand: print the effective configuration, i.e. the
+/// configuration the application was booted with (the merged configuration
+/// sources after `Configurable::process_config`).
+#[derive(Debug, Parser)]
+pub struct ShowCmd<A>
+where
+    A: StaticApp,
+    A::Cfg: CommandConfig,
+{
+    /// Application type
+    #[arg(skip)]
+    app: PhantomData<A>,
+}
+
+impl<A> Runnable for ShowCmd<A>
+where
+    A: StaticApp,
+    A::Cfg: CommandConfig,
+{
+    fn run(&self) {
+        print_config(&*A::app_cell().config());
+    }
+}
+
+/// `config check` subcommand: validate a configuration file, exiting with a
+/// nonzero status if it's invalid.
+///
+/// The file is loaded in place of the application's configuration sources
+/// using its `Application::config_loader` (e.g. applying its migrations and
+/// handling of unknown keys).
+#[derive(Debug, Parser)]
+pub struct CheckCmd<A>
+where
+    A: StaticApp,
+    A::Cfg: CommandConfig,
+{
+    /// Path to the configuration file to check
+    path: PathBuf,
+
+    /// Application type
+    #[arg(skip)]
+    app: PhantomData<A>,
+}
+
+impl<A> CheckCmd<A>
+where
+    A: StaticApp,
+    A::Cfg: CommandConfig,
+{
+    /// Load the file with the application's configuration loader.
+    fn check(&self) -> Result<A::Cfg, FrameworkError> {
+        let app = A::app_cell();
+        let command = app
+            .state()
+            .command()
+            .ok_or_else(|| format_err!(ConfigError, "{} hasn't been booted", app.name()))?;
+
+        app.config_loader(command)
+            .clear_sources()
+            .source(Source::required(&self.path))
+            .load()
+    }
+}
+
+impl<A> Runnable for CheckCmd<A>
+where
+    A: StaticApp,
+    A::Cfg: CommandConfig,
+{
+    fn run(&self) {
+        match self.check() {
+            Ok(_) => {
+                status_ok!("Checked", "{}", self.path.display());
+            }
+            Err(e) => exit_with_error(&e),
+        }
+    }
+}
+
+/// `config explain` subcommand: print the effective value(s) of the given
+/// configuration key along with where they came from.
+#[derive(Debug, Parser)]
+pub struct ExplainCmd<A>
+where
+    A: StaticApp,
+    A::Cfg: CommandConfig,
+{
+    /// Key path to explain (e.g. `database.url`), or a table to explain all
+    /// of the keys within it
+    key: String,
+
+    /// Application type
+    #[arg(skip)]
+    app: PhantomData<A>,
+}
+
+impl<A> Runnable for ExplainCmd<A>
+where
+    A: StaticApp,
+    A::Cfg: CommandConfig,
+{
+    fn run(&self) {
+        let app = A::app_cell();
+        let (table, provenance) = match provenance::to_table(&*app.config())
+            .and_then(|table| Ok((table, app.config_provenance()?)))
+        {
+            Ok(result) => result,
+            Err(e) => exit_with_error(&e),
+        };
+
+        let prefix = format!("{}.", self.key);
+        let mut found = false;
+
+        for (key_path, value) in provenance::leaves(&table) {
+            let key = key_path.join(".");
+
+            if key == self.key || key.starts_with(&prefix) {
+                println!("{} = {}", key, value);
+                println!("    origin: {}", provenance.origin(&key));
+                found = true;
+            }
+        }
+
+        if !found {
+            status_err!("unknown config key `{}`", self.key);
+            process::exit(1);
+        }
+    }
+}
+
+/// `config migrate` subcommand: rewrite a configuration file in place,
+/// migrating it to the latest version.
+///
+/// Note that comments and formatting in the file are not preserved.
+#[derive(Debug, Parser)]
+pub struct MigrateCmd<A>
+where
+    A: StaticApp,
+    A::Cfg: CommandConfig,
+{
+    /// Path to the configuration file to migrate
+    path: PathBuf,
+
+    /// Application type
+    #[arg(skip)]
+    app: PhantomData<A>,
+}
+
+impl<A> MigrateCmd<A>
+where
+    A: StaticApp,
+    A::Cfg: CommandConfig,
+{
+    /// Migrate the file, returning the version it was migrated to (if it
+    /// was outdated).
+    fn migrate(&self) -> Result<Option<u32>, FrameworkError> {
+        let migrations = A::app_cell().config_migrations();
+        let path = AbsPathBuf::canonicalize(&self.path)?;
+        let format = Format::from_path(path.as_path())?;
+        let table = format.parse_table(&read_file(path.as_ref())?, Some(path.as_path()))?;
+
+        let (table, applied) = migrations.migrate(table)?;
+
+        if applied.is_empty() {
+            return Ok(None);
+        }
+
+        // Ensure the migrated configuration is valid before overwriting it
+        let value = Value::Table(table);
+        value
+            .clone()
+            .try_into::<A::Cfg>()
+            .map_err(|e| format_err!(ConfigError, "migrated config is invalid: {}", e))?;
+
+        crate::fs::write(path.as_path(), format.serialize(&value)?)?;
+        Ok(Some(migrations.latest_version()))
+    }
+}
+
+impl<A> Runnable for MigrateCmd<A>
+where
+    A: StaticApp,
+    A::Cfg: CommandConfig,
+{
+    fn run(&self) {
+        match self.migrate() {
+            Ok(Some(version)) => {
+                status_ok!("Migrated", "{} to version {}", self.path.display(), version);
+            }
+            Ok(None) => {
+                status_ok!("Fresh", "{} is up-to-date", self.path.display());
+            }
+            Err(e) => exit_with_error(&e),
+        }
+    }
+}
+
+/// `config schema` subcommand: print the JSON Schema for the configuration
+/// type `C`.
+#[cfg(feature = "schema")]
+#[derive(Debug, Parser)]
+pub struct SchemaCmd<C>
+where
+    C: Config + JsonSchema + 'static,
+{
+    /// Write the schema to the given file instead of standard output
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Configuration type
+    #[arg(skip)]
+    config: PhantomData<C>,
+}
+
+#[cfg(feature = "schema")]
+impl<C> Runnable for SchemaCmd<C>
+where
+    C: Config + JsonSchema + 'static,
+{
+    fn run(&self) {
+        let result = schema::to_json::<C>().and_then(|json| match &self.output {
+            Some(path) => crate::fs::write(path, json).map_err(Into::into),
+            None => {
+                print!("{}", json);
+                Ok(())
+            }
+        });
+
+        if let Err(e) = result {
+            exit_with_error(&e);
+        }
+    }
+}
+
+/// Print the given configuration to standard output as TOML.
+fn print_config<C: Serialize>(config: &C) {
+    match Format::Toml.serialize(config) {
+        Ok(toml) => print!("{}", toml),
+        Err(e) => exit_with_error(&e),
+    }
+}
+
+/// Print the given error (along with any config file diagnostic) and exit
+/// with its exit code.
+fn exit_with_error(err: &FrameworkError) -> ! {
+    status_err!("{}", err);
+    exit::print_diagnostic(err);
+    process::exit(err.exit_code());
+}
+
+#[cfg(all(test, feature = "schema"))]
+mod tests {
+    use super::*;
+    use crate::{
+        application::{self, AppCell},
+        config::{self, CfgCell, Configurable},
+        Application, Command, StandardPaths,
+    };
+    use serde::Deserialize;
+    use std::env;
+
+    /// Example configuration
+    #[derive(Debug, Default, Deserialize, Serialize, JsonSchema)]
+    struct ExampleConfig {
+        /// Example value
+        value: u32,
+    }
+
+    /// Example application state
+    static APP: AppCell<ExampleApp> = AppCell::new();
+
+    /// Example application's command
+    #[derive(Debug, Parser)]
+    struct ExampleCmd {
+        #[command(subcommand)]
+        config: ConfigCmd<ExampleApp>,
+    }
+
+    impl Command for ExampleCmd {
+        fn name() -> &'static str {
+            "example"
+        }
+
+        fn description() -> &'static str {
+            ""
+        }
+
+        fn authors() -> &'static str {
+            ""
+        }
+    }
+
+    impl Configurable<ExampleConfig> for ExampleCmd {
+        fn config_path(&self) -> Option<PathBuf> {
+            None
+        }
+    }
+
+    impl Runnable for ExampleCmd {
+        fn run(&self) {
+            self.config.run()
+        }
+    }
+
+    /// Example application
+    #[derive(Debug, Default)]
+    struct ExampleApp {
+        config: CfgCell<ExampleConfig>,
+        state: application::State<Self>,
+    }
+
+    impl Application for ExampleApp {
+        type Cmd = ExampleCmd;
+        type Cfg = ExampleConfig;
+        type Paths = StandardPaths;
+
+        fn config(&self) -> config::Reader<ExampleConfig> {
+            self.config.read()
+        }
+
+        fn state(&self) -> &application::State<Self> {
+            &self.state
+        }
+
+        fn register_components(&mut self, _command: &ExampleCmd) -> Result<(), FrameworkError> {
+            Ok(())
+        }
+
+        fn after_config(&mut self, config: ExampleConfig) -> Result<(), FrameworkError> {
+            self.config.set_once(config);
+            Ok(())
+        }
+    }
+
+    impl StaticApp for ExampleApp {
+        fn app_cell() -> &'static AppCell<Self> {
+            &APP
+        }
+    }
+
+    #[test]
+    fn config_schema_subcommand() {
+        let path = env::temp_dir().join(format!("abscissa-config-schema-{}.json", process::id()));
+        ExampleCmd::parse_from(["example", "schema", "-o", path.to_str().unwrap()]).run();
+
+        let schema = crate::fs::read_to_string(&path).unwrap();
+        assert_eq!(schema, schema::to_json::<ExampleConfig>().unwrap());
+
+        crate::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn schema_to_file() {
+        let path = env::temp_dir().join(format!("abscissa-schema-{}.json", process::id()));
+        SchemaCmd::<ExampleConfig>::parse_from(["schema", "-o", path.to_str().unwrap()]).run();
+
+        let schema = crate::fs::read_to_string(&path).unwrap();
+        assert_eq!(schema, schema::to_json::<ExampleConfig>().unwrap());
+        assert!(schema.contains("Example value"));
+
+        crate::fs::remove_file(path).unwrap();
+    }
+}
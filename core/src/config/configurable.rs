@@ -1,16 +1,43 @@
 //! Configuration loader
 
-use super::Config;
+use super::{Assignment, Config, Source};
 use crate::FrameworkError;
 use std::path::PathBuf;
 
 /// Command type with which a configuration file is associated
 pub trait Configurable<Cfg: Config> {
-    /// Path to the command's configuration file. Returns an error by default.
+    /// Path to the command's configuration file, or `-` to read it from
+    /// standard input. Returns `None` by default.
     fn config_path(&self) -> Option<PathBuf> {
         None
     }
 
+    /// Configuration sources for this command, in order of increasing
+    /// precedence. These are deep-merged before being deserialized.
+    ///
+    /// By default this is the path returned by `config_path` (if any), which
+    /// is required to exist. See `Source::standard` for the conventional
+    /// system/user/project precedence chain.
+    fn config_sources(&self) -> Vec<Source> {
+        self.config_path()
+            .into_iter()
+            .map(Source::required)
+            .collect()
+    }
+
+    /// Name of the configuration profile selected by this command (e.g. with
+    /// [`ProfileArgs`][`super::profile::ProfileArgs`]), if any.
+    fn config_profile(&self) -> Option<String> {
+        None
+    }
+
+    /// Assignments of configuration values made by this command (e.g. with
+    /// [`SetArgs`][`super::assignment::SetArgs`]), applied after all other
+    /// sources.
+    fn config_assignments(&self) -> Vec<Assignment> {
+        vec![]
+    }
+
     /// Process the configuration after it has been loaded, potentially
     /// modifying it or returning an error if options are incompatible
     fn process_config(&self, config: Cfg) -> Result<Cfg, FrameworkError> {
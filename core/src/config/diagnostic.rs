@@ -0,0 +1,345 @@
+//! Configuration diagnostics: errors which point at the offending location
+//! in a configuration file.
+
+use std::{
+    fmt::{self, Display},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// Error in a configuration file, annotated with the file's path, the key
+/// path of the offending value (e.g. `hello.recipient`), and its location.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Diagnostic {
+    /// Description of the error
+    message: String,
+
+    /// Path to the configuration file (if loaded from a file)
+    path: Option<PathBuf>,
+
+    /// Key path of the offending value (if known)
+    key: Option<String>,
+
+    /// Zero-based line and column of the offending value (if known)
+    location: Option<(usize, usize)>,
+
+    /// Source line containing the offending value
+    line: Option<String>,
+}
+
+impl Diagnostic {
+    /// Create a new diagnostic with the given message.
+    pub fn new(message: impl ToString) -> Self {
+        Self {
+            message: message.to_string(),
+            path: None,
+            key: None,
+            location: None,
+            line: None,
+        }
+    }
+
+    /// Create a diagnostic from an error which occurred parsing the given
+    /// TOML text.
+    pub fn from_toml_error(err: &toml::de::Error, text: &str) -> Self {
+        let mut message = err.to_string();
+
+        // Strip the location suffix added by `toml`, as we render it ourselves
+        if let Some((line, column)) = err.line_col() {
+            strip_location(&mut message, line + 1, column + 1);
+        }
+
+        let key = strip_key(&mut message);
+        let mut diagnostic = Self::new(message);
+        diagnostic.key = key;
+
+        if let Some((line, column)) = err.line_col() {
+            diagnostic = diagnostic.with_location(text, line, column);
+        }
+
+        diagnostic
+    }
+
+    /// Create a diagnostic from an error which occurred parsing the given
+    /// JSON text.
+    #[cfg(feature = "json")]
+    pub fn from_json_error(err: &serde_json::Error, text: &str) -> Self {
+        let mut message = err.to_string();
+
+        // `serde_json` uses a line number of 0 for errors with no location
+        if err.line() == 0 {
+            return Self::new(message);
+        }
+
+        strip_location(&mut message, err.line(), err.column());
+        Self::new(message).with_location(text, err.line() - 1, err.column().saturating_sub(1))
+    }
+
+    /// Create a diagnostic from an error which occurred parsing the given
+    /// YAML text.
+    #[cfg(feature = "yaml")]
+    pub fn from_yaml_error(err: &serde_yaml::Error, text: &str) -> Self {
+        let mut message = err.to_string();
+
+        match err.location() {
+            Some(location) if location.line() > 0 => {
+                strip_location(&mut message, location.line(), location.column());
+                Self::new(message).with_location(
+                    text,
+                    location.line() - 1,
+                    location.column().saturating_sub(1),
+                )
+            }
+            _ => Self::new(message),
+        }
+    }
+
+    /// Create a diagnostic from an error which occurred parsing the given
+    /// RON text.
+    #[cfg(feature = "ron")]
+    pub fn from_ron_error(err: &serde_ron::error::SpannedError, text: &str) -> Self {
+        let diagnostic = Self::new(&err.code);
+
+        // `ron` uses a position of 0:0 for errors with no location
+        match err.position {
+            serde_ron::error::Position { line: 0, .. } => diagnostic,
+            serde_ron::error::Position { line, col } => {
+                diagnostic.with_location(text, line - 1, col.saturating_sub(1))
+            }
+        }
+    }
+
+    /// Set the path to the configuration file this diagnostic refers to.
+    pub fn with_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// Set the key path of the offending value.
+    pub fn with_key(mut self, key: impl ToString) -> Self {
+        self.key = Some(key.to_string());
+        self
+    }
+
+    /// Set the (zero-based) location of the offending value in `text`.
+    pub fn with_location(mut self, text: &str, line: usize, column: usize) -> Self {
+        self.location = Some((line, column));
+        self.line = text.lines().nth(line).map(ToOwned::to_owned);
+        self
+    }
+
+    /// Get the error message.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Get the path to the configuration file (if known).
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Get the key path of the offending value (if known).
+    pub fn key(&self) -> Option<&str> {
+        self.key.as_deref()
+    }
+
+    /// Get the one-based line and column of the offending value (if known).
+    pub fn line_col(&self) -> Option<(usize, usize)> {
+        self.location.map(|(line, column)| (line + 1, column + 1))
+    }
+
+    /// Write an annotated snippet of the offending line, e.g.
+    ///
+    /// ```text
+    ///   --> /etc/myapp/myapp.toml:2:13
+    ///    |
+    ///  2 | recipient = 42
+    ///    |             ^ invalid type: integer `42`, expected a string
+    /// ```
+    pub fn write_snippet(&self, w: &mut impl Write) -> io::Result<()> {
+        let path = self
+            .path
+            .as_ref()
+            .map(|path| path.display().to_string())
+            .unwrap_or_else(|| "<string>".to_owned());
+
+        let (line_no, column, line) = match (self.line_col(), &self.line) {
+            (Some((line_no, column)), Some(line)) => (line_no, column, line),
+            _ if self.path.is_some() => return writeln!(w, "  --> {}", path),
+            _ => return Ok(()),
+        };
+
+        let gutter = " ".repeat(line_no.to_string().len());
+        writeln!(w, "{} --> {}:{}:{}", gutter, path, line_no, column)?;
+        writeln!(w, "{} |", gutter)?;
+        writeln!(w, "{} | {}", line_no, line)?;
+        writeln!(
+            w,
+            "{} | {}^ {}",
+            gutter,
+            " ".repeat(column - 1),
+            self.message
+        )
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)?;
+
+        if let Some(key) = &self.key {
+            write!(f, " for key `{}`", key)?;
+        }
+
+        match (&self.path, self.line_col()) {
+            (Some(path), Some((line, column))) => {
+                write!(f, " in {}:{}:{}", path.display(), line, column)
+            }
+            (Some(path), None) => write!(f, " in {}", path.display()),
+            (None, Some((line, column))) => write!(f, " at line {} column {}", line, column),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+/// Strip the (one-based) ` at line N column M` suffix added by many
+/// deserializers from an error message, as we render it ourselves.
+fn strip_location(message: &mut String, line: usize, column: usize) {
+    let suffix = format!(" at line {} column {}", line, column);
+
+    if message.ends_with(&suffix) {
+        message.truncate(message.len() - suffix.len());
+    }
+}
+
+/// Strip the ``for key `...` `` suffix added by `toml` from an error message,
+/// returning the key path it contained.
+pub(crate) fn strip_key(message: &mut String) -> Option<String> {
+    if !message.ends_with('`') {
+        return None;
+    }
+
+    let pos = message.rfind(" for key `")?;
+    let key = message[(pos + 10)..(message.len() - 1)].to_owned();
+    message.truncate(pos);
+    Some(key)
+}
+
+/// Locate the definition of the value with the given key path in TOML text,
+/// returning its zero-based line and column.
+///
+/// This is a line-based heuristic which handles table headers and (dotted)
+/// keys, falling back to the closest enclosing definition.
+pub(crate) fn locate(text: &str, key_path: &[String]) -> Option<(usize, usize)> {
+    let mut table = vec![];
+    let mut best = None;
+    let mut best_score = 0;
+
+    for (line_no, line) in text.lines().enumerate() {
+        let trimmed = line.trim_start();
+        let indent = line.len() - trimmed.len();
+
+        let (path, column) = if trimmed.starts_with('[') {
+            let header = trimmed.trim_start_matches('[');
+            table = split_key(&header[..header.find(']').unwrap_or(header.len())]);
+            (table.clone(), indent)
+        } else if trimmed.starts_with('#') {
+            continue;
+        } else if let Some(eq) = trimmed.find('=') {
+            let value = &trimmed[(eq + 1)..];
+            let column = indent + eq + 1 + (value.len() - value.trim_start().len());
+            let mut path = table.clone();
+            path.extend(split_key(&trimmed[..eq]));
+            (path, column)
+        } else {
+            continue;
+        };
+
+        // Prefer exact matches, then the closest enclosing definition, and
+        // finally the first definition nested under the key path
+        let score = if key_path.starts_with(&path) {
+            path.len() * 2
+        } else if path.starts_with(key_path) {
+            key_path.len() * 2 - 1
+        } else {
+            0
+        };
+
+        if score > best_score {
+            best_score = score;
+            best = Some((line_no, column));
+        }
+    }
+
+    best
+}
+
+/// Split a (possibly dotted and/or quoted) TOML key into its segments.
+fn split_key(key: &str) -> Vec<String> {
+    let mut segments = vec![];
+    let mut segment = String::new();
+    let mut quote = None;
+
+    for c in key.chars() {
+        match (quote, c) {
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (Some(q), c) if q == c => quote = None,
+            (None, '.') => segments.push(segment.split_off(0).trim().to_owned()),
+            _ => segment.push(c),
+        }
+    }
+
+    segments.push(segment.trim().to_owned());
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE_TOML: &str = "name = \"example\"\n\n[hello]\nrecipient = 42\n";
+
+    fn key_path(key: &str) -> Vec<String> {
+        key.split('.').map(ToOwned::to_owned).collect()
+    }
+
+    #[test]
+    fn locate_key() {
+        assert_eq!(
+            locate(EXAMPLE_TOML, &key_path("hello.recipient")),
+            Some((3, 12))
+        );
+        assert_eq!(locate(EXAMPLE_TOML, &key_path("hello")), Some((2, 0)));
+        assert_eq!(
+            locate(EXAMPLE_TOML, &key_path("hello.missing")),
+            Some((2, 0))
+        );
+        assert_eq!(locate(EXAMPLE_TOML, &key_path("other")), None);
+    }
+
+    #[test]
+    fn snippet() {
+        let diagnostic = Diagnostic::new("invalid type: integer `42`, expected a string")
+            .with_path("/etc/example.toml")
+            .with_key("hello.recipient")
+            .with_location(EXAMPLE_TOML, 3, 12);
+
+        assert_eq!(
+            diagnostic.to_string(),
+            "invalid type: integer `42`, expected a string \
+             for key `hello.recipient` in /etc/example.toml:4:13"
+        );
+
+        let mut snippet = vec![];
+        diagnostic.write_snippet(&mut snippet).unwrap();
+        assert_eq!(
+            String::from_utf8(snippet).unwrap(),
+            "  --> /etc/example.toml:4:13\n  \
+             |\n\
+             4 | recipient = 42\n  \
+             |             ^ invalid type: integer `42`, expected a string\n"
+        );
+    }
+}
@@ -0,0 +1,199 @@
+//! Environment variable overlay for configuration
+//!
+//! Variables named `<PREFIX>__<KEY>__<SUBKEY>` are mapped onto the
+//! (lowercased) nested key `key.subkey` of the configuration.
+
+use crate::{FrameworkError, FrameworkErrorKind::ConfigError};
+use std::env;
+use toml::value::{Table, Value};
+
+/// Separator between the prefix and the segments of a key path
+pub const SEPARATOR: &str = "__";
+
+/// Compute the default environment variable prefix for the application
+/// with the given name, e.g. `my-app` => `MY_APP`.
+pub fn default_prefix(app_name: &str) -> String {
+    app_name.to_uppercase().replace('-', "_")
+}
+
+/// Configuration value set from an environment variable.
+#[derive(Clone, Debug)]
+pub(super) struct EnvVar {
+    /// Name of the environment variable
+    pub name: String,
+
+    /// Key path the variable is mapped onto
+    pub path: Vec<String>,
+
+    /// Raw value of the variable
+    pub value: String,
+
+    /// Was the type of this value inferred (i.e. not known from the config)?
+    pub inferred: bool,
+}
+
+impl EnvVar {
+    /// Collect all variables with the given prefix from the environment.
+    pub fn collect(prefix: &str) -> Result<Vec<Self>, FrameworkError> {
+        let var_prefix = format!("{}{}", prefix, SEPARATOR);
+        let mut vars = vec![];
+
+        for (name, value) in env::vars_os() {
+            let name = match name.into_string() {
+                Ok(name) if name.starts_with(&var_prefix) => name,
+                _ => continue,
+            };
+
+            let value = value
+                .into_string()
+                .map_err(|_| format_err!(ConfigError, "{}: value is not valid UTF-8", name))?;
+
+            let path = name[var_prefix.len()..]
+                .split(SEPARATOR)
+                .map(str::to_lowercase)
+                .collect::<Vec<_>>();
+
+            ensure!(
+                path.iter().all(|segment| !segment.is_empty()),
+                ConfigError,
+                "{}: malformed key path",
+                name
+            );
+
+            vars.push(Self {
+                name,
+                path,
+                value,
+                inferred: false,
+            });
+        }
+
+        // Sort for deterministic precedence when paths overlap
+        vars.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(vars)
+    }
+
+    /// Set this variable's value in the given table, coercing it to the type
+    /// of the value it replaces if present, and inferring it otherwise.
+    pub fn apply(&mut self, table: &mut Table) -> Result<(), FrameworkError> {
+        let (key, parents) = self.path.split_last().expect("empty key path");
+        let mut table = table;
+
+        for segment in parents {
+            let value = table
+                .entry(segment.clone())
+                .or_insert_with(|| Value::Table(Table::new()));
+
+            table = match value {
+                Value::Table(t) => t,
+                _ => fail!(ConfigError, "{}: `{}` is not a table", self.name, segment),
+            };
+        }
+
+        let value = match table.get(key) {
+            Some(existing) => self.coerce(existing)?,
+            None => {
+                let value = infer(&self.value);
+                self.inferred = !value.is_str();
+                value
+            }
+        };
+
+        table.insert(key.clone(), value);
+        Ok(())
+    }
+
+    /// Set this variable's value as a string, e.g. after an inferred type
+    /// turned out to be wrong.
+    pub fn apply_as_str(&mut self, table: &mut Table) {
+        let mut value = table;
+        let (key, parents) = self.path.split_last().expect("empty key path");
+
+        for segment in parents {
+            value = value
+                .get_mut(segment)
+                .and_then(Value::as_table_mut)
+                .expect("env var not applied");
+        }
+
+        value.insert(key.clone(), Value::String(self.value.clone()));
+        self.inferred = false;
+    }
+
+    /// Coerce this variable's value to the same type as `existing`.
+    fn coerce(&self, existing: &Value) -> Result<Value, FrameworkError> {
+        if existing.is_str() {
+            return Ok(Value::String(self.value.clone()));
+        }
+
+        match parse_literal(&self.value) {
+            Some(value) if value.same_type(existing) => Ok(value),
+            // Allow integers where floats are expected
+            Some(Value::Integer(n)) if existing.is_float() => Ok(Value::Float(n as f64)),
+            _ => fail!(
+                ConfigError,
+                "{}: expected {}, got {:?}",
+                self.name,
+                existing.type_str(),
+                self.value
+            ),
+        }
+    }
+}
+
+/// Infer the type of a value, falling back to a string if it isn't a valid
+/// TOML literal.
+pub(super) fn infer(value: &str) -> Value {
+    parse_literal(value).unwrap_or_else(|| Value::String(value.to_owned()))
+}
+
+/// Parse a value as a TOML literal (e.g. `42`, `true`, `[1, 2]`).
+fn parse_literal(value: &str) -> Option<Value> {
+    let mut table = toml::from_str::<Table>(&format!("value = {}", value)).ok()?;
+    table.remove("value")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env_var(path: &str, value: &str) -> EnvVar {
+        EnvVar {
+            name: format!("APP__{}", path.to_uppercase().replace('.', "__")),
+            path: path.split('.').map(str::to_owned).collect(),
+            value: value.to_owned(),
+            inferred: false,
+        }
+    }
+
+    #[test]
+    fn apply_nested() {
+        let mut table: Table = toml::from_str("[database]\nport = 5432\nhost = \"db\"").unwrap();
+
+        env_var("database.port", "6543").apply(&mut table).unwrap();
+        env_var("database.host", "42").apply(&mut table).unwrap();
+        env_var("server.tls.enabled", "true")
+            .apply(&mut table)
+            .unwrap();
+
+        let expected: Table =
+            toml::from_str("[database]\nport = 6543\nhost = \"42\"\n[server.tls]\nenabled = true")
+                .unwrap();
+        assert_eq!(table, expected);
+    }
+
+    #[test]
+    fn apply_bad_value() {
+        let mut table: Table = toml::from_str("[database]\nport = 5432").unwrap();
+        let err = env_var("database.port", "many")
+            .apply(&mut table)
+            .unwrap_err();
+        assert_eq!(*err.kind(), ConfigError);
+        assert!(err.to_string().contains("APP__DATABASE__PORT"));
+    }
+
+    #[test]
+    fn default_prefix_from_name() {
+        assert_eq!(default_prefix("my-app"), "MY_APP");
+    }
+}
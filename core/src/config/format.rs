@@ -0,0 +1,370 @@
+//! Configuration file formats
+
+use super::Diagnostic;
+use crate::{FrameworkError, FrameworkErrorKind::ConfigError};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{ffi::OsStr, fmt, path::Path};
+use toml::value::Table;
+
+#[cfg(any(feature = "json", feature = "yaml", feature = "ron"))]
+use {
+    serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor},
+    std::convert::TryFrom,
+    toml::Value,
+};
+
+/// Configuration file format.
+///
+/// TOML is always supported. Other formats are enabled with the cargo
+/// feature of the same name (`json`, `yaml`, `ron`).
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Format {
+    /// TOML (`.toml`)
+    #[default]
+    Toml,
+
+    /// JSON (`.json`)
+    #[cfg(feature = "json")]
+    Json,
+
+    /// YAML (`.yaml`, `.yml`)
+    #[cfg(feature = "yaml")]
+    Yaml,
+
+    /// Rusty Object Notation (`.ron`)
+    #[cfg(feature = "ron")]
+    Ron,
+}
+
+impl Format {
+    /// Get the enabled format for the given file extension (if any).
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "toml" => Some(Format::Toml),
+            #[cfg(feature = "json")]
+            "json" => Some(Format::Json),
+            #[cfg(feature = "yaml")]
+            "yaml" | "yml" => Some(Format::Yaml),
+            #[cfg(feature = "ron")]
+            "ron" => Some(Format::Ron),
+            _ => None,
+        }
+    }
+
+    /// Determine the format of the configuration file at the given path from
+    /// its extension.
+    ///
+    /// Files with an unrecognized (or no) extension are assumed to be TOML.
+    /// Files with the extension of a format whose cargo feature isn't
+    /// enabled result in a `ConfigError`.
+    pub fn from_path(path: &Path) -> Result<Self, FrameworkError> {
+        let extension = match path.extension().and_then(OsStr::to_str) {
+            Some(extension) => extension,
+            None => return Ok(Format::Toml),
+        };
+
+        if let Some(format) = Self::from_extension(extension) {
+            return Ok(format);
+        }
+
+        let feature = match extension.to_ascii_lowercase().as_str() {
+            "json" => "json",
+            "yaml" | "yml" => "yaml",
+            "ron" => "ron",
+            _ => return Ok(Format::Toml),
+        };
+
+        fail!(
+            ConfigError,
+            "{}: support for .{} config files is not enabled (requires the `{}` feature)",
+            path.display(),
+            extension,
+            feature
+        )
+    }
+
+    /// Get the canonical file extension for this format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Toml => "toml",
+            #[cfg(feature = "json")]
+            Format::Json => "json",
+            #[cfg(feature = "yaml")]
+            Format::Yaml => "yaml",
+            #[cfg(feature = "ron")]
+            Format::Ron => "ron",
+        }
+    }
+
+    /// Parse text in this format, annotating errors with a [`Diagnostic`]
+    /// which locates them within the text (and the file at the given path,
+    /// if any).
+    pub fn parse<T>(self, text: &str, path: Option<&Path>) -> Result<T, FrameworkError>
+    where
+        T: DeserializeOwned,
+    {
+        let result = match self {
+            Format::Toml => toml::from_str(text).map_err(|e| Diagnostic::from_toml_error(&e, text)),
+            #[cfg(feature = "json")]
+            Format::Json => {
+                serde_json::from_str(text).map_err(|e| Diagnostic::from_json_error(&e, text))
+            }
+            #[cfg(feature = "yaml")]
+            Format::Yaml => {
+                serde_yaml::from_str(text).map_err(|e| Diagnostic::from_yaml_error(&e, text))
+            }
+            #[cfg(feature = "ron")]
+            Format::Ron => {
+                serde_ron::from_str(text).map_err(|e| Diagnostic::from_ron_error(&e, text))
+            }
+        };
+
+        result.map_err(|mut diagnostic| {
+            if let Some(path) = path {
+                diagnostic = diagnostic.with_path(path);
+            }
+
+            ConfigError.context(diagnostic).into()
+        })
+    }
+
+    /// Parse a table in this format, as with `parse`.
+    ///
+    /// TOML has no null values, so the null (or unit) values of other formats
+    /// are removed from tables, e.g. so `Option` fields default to `None` as
+    /// they would when deserializing the file directly.
+    pub fn parse_table(self, text: &str, path: Option<&Path>) -> Result<Table, FrameworkError> {
+        match self {
+            Format::Toml => self.parse(text, path),
+            #[cfg(feature = "json")]
+            Format::Json => self.parse(text, path).map(|NullFreeTable(table)| table),
+            #[cfg(feature = "yaml")]
+            Format::Yaml => self.parse(text, path).map(|NullFreeTable(table)| table),
+            #[cfg(feature = "ron")]
+            Format::Ron => self.parse(text, path).map(|NullFreeTable(table)| table),
+        }
+    }
+
+    /// Serialize the given value in this format.
+    pub fn serialize<T>(self, value: &T) -> Result<String, FrameworkError>
+    where
+        T: Serialize,
+    {
+        let result = match self {
+            Format::Toml => toml::to_string_pretty(value).map_err(|e| e.to_string()),
+            #[cfg(feature = "json")]
+            Format::Json => serde_json::to_string_pretty(value).map_err(|e| e.to_string()),
+            #[cfg(feature = "yaml")]
+            Format::Yaml => serde_yaml::to_string(value).map_err(|e| e.to_string()),
+            #[cfg(feature = "ron")]
+            Format::Ron => {
+                serde_ron::ser::to_string_pretty(value, serde_ron::ser::PrettyConfig::default())
+                    .map_err(|e| e.to_string())
+            }
+        };
+
+        result.map_err(|e| {
+            format_err!(ConfigError, "error serializing config as {}: {}", self, e).into()
+        })
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Format::Toml => "TOML",
+            #[cfg(feature = "json")]
+            Format::Json => "JSON",
+            #[cfg(feature = "yaml")]
+            Format::Yaml => "YAML",
+            #[cfg(feature = "ron")]
+            Format::Ron => "RON",
+        })
+    }
+}
+
+/// Table deserialized from a format other than TOML, without null values.
+#[cfg(any(feature = "json", feature = "yaml", feature = "ron"))]
+struct NullFreeTable(Table);
+
+#[cfg(any(feature = "json", feature = "yaml", feature = "ron"))]
+impl<'de> Deserialize<'de> for NullFreeTable {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match NullableValue::deserialize(deserializer)? {
+            NullableValue(Some(Value::Table(table))) => Ok(NullFreeTable(table)),
+            _ => Err(de::Error::custom("expected a table")),
+        }
+    }
+}
+
+/// TOML value which may be null, deserialized from a format other than TOML
+/// (removing null values from tables).
+#[cfg(any(feature = "json", feature = "yaml", feature = "ron"))]
+struct NullableValue(Option<Value>);
+
+#[cfg(any(feature = "json", feature = "yaml", feature = "ron"))]
+impl<'de> Deserialize<'de> for NullableValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(NullableValueVisitor)
+    }
+}
+
+/// Visitor for [`NullableValue`]
+#[cfg(any(feature = "json", feature = "yaml", feature = "ron"))]
+struct NullableValueVisitor;
+
+#[cfg(any(feature = "json", feature = "yaml", feature = "ron"))]
+impl<'de> Visitor<'de> for NullableValueVisitor {
+    type Value = NullableValue;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a configuration value")
+    }
+
+    fn visit_bool<E: de::Error>(self, value: bool) -> Result<NullableValue, E> {
+        Ok(NullableValue(Some(Value::Boolean(value))))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<NullableValue, E> {
+        Ok(NullableValue(Some(Value::Integer(value))))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<NullableValue, E> {
+        match i64::try_from(value) {
+            Ok(value) => self.visit_i64(value),
+            Err(_) => Err(E::custom(format!("integer out of range: {}", value))),
+        }
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<NullableValue, E> {
+        Ok(NullableValue(Some(Value::Float(value))))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<NullableValue, E> {
+        self.visit_string(value.to_owned())
+    }
+
+    fn visit_string<E: de::Error>(self, value: String) -> Result<NullableValue, E> {
+        Ok(NullableValue(Some(Value::String(value))))
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<NullableValue, E> {
+        Ok(NullableValue(None))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<NullableValue, E> {
+        Ok(NullableValue(None))
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<NullableValue, D::Error> {
+        NullableValue::deserialize(deserializer)
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<NullableValue, D::Error> {
+        NullableValue::deserialize(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<NullableValue, A::Error> {
+        let mut array = Vec::new();
+
+        while let Some(NullableValue(value)) = seq.next_element()? {
+            match value {
+                Some(value) => array.push(value),
+                None => return Err(de::Error::custom("arrays can't contain null values")),
+            }
+        }
+
+        Ok(NullableValue(Some(Value::Array(array))))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<NullableValue, A::Error> {
+        let mut table = Table::new();
+
+        while let Some((key, NullableValue(value))) = map.next_entry::<String, _>()? {
+            if let Some(value) = value {
+                table.insert(key, value);
+            }
+        }
+
+        Ok(NullableValue(Some(Value::Table(table))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_path() {
+        assert_eq!(
+            Format::from_path(Path::new("app.toml")).unwrap(),
+            Format::Toml
+        );
+        assert_eq!(
+            Format::from_path(Path::new("app.conf")).unwrap(),
+            Format::Toml
+        );
+        assert_eq!(Format::from_path(Path::new("app")).unwrap(), Format::Toml);
+
+        #[cfg(feature = "json")]
+        assert_eq!(
+            Format::from_path(Path::new("app.JSON")).unwrap(),
+            Format::Json
+        );
+
+        #[cfg(not(feature = "yaml"))]
+        assert!(Format::from_path(Path::new("app.yml")).is_err());
+    }
+
+    #[test]
+    fn round_trip() {
+        let formats = [
+            Format::Toml,
+            #[cfg(feature = "json")]
+            Format::Json,
+            #[cfg(feature = "yaml")]
+            Format::Yaml,
+            #[cfg(feature = "ron")]
+            Format::Ron,
+        ];
+
+        let mut table = Table::new();
+        table.insert("name".to_owned(), "example".into());
+        table.insert("port".to_owned(), 8080.into());
+
+        for format in formats {
+            let text = format.serialize(&table).unwrap();
+            let parsed = format.parse_table(&text, None).unwrap();
+            assert_eq!(parsed, table, "{} round trip", format);
+        }
+    }
+
+    #[test]
+    fn parse_table_without_nulls() {
+        let cases: &[(Format, &str)] = &[
+            #[cfg(feature = "json")]
+            (
+                Format::Json,
+                "{\"name\": null, \"port\": 8080, \"server\": {\"host\": null}}",
+            ),
+            #[cfg(feature = "yaml")]
+            (Format::Yaml, "name: ~\nport: 8080\nserver:\n  host: null\n"),
+            #[cfg(feature = "ron")]
+            (
+                Format::Ron,
+                "{\"name\": (), \"port\": 8080, \"server\": {\"host\": None}}",
+            ),
+        ];
+
+        let expected: Table = toml::from_str("port = 8080\n\n[server]\n").unwrap();
+
+        for &(format, text) in cases {
+            let table = format.parse_table(text, None).unwrap();
+            assert_eq!(table, expected, "{} nulls", format);
+        }
+    }
+}
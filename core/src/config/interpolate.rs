@@ -0,0 +1,204 @@
+//! Interpolation of references inside configuration string values:
+//!
+//! - `${VAR}`: value of the environment variable `VAR`
+//! - `${VAR:-default}`: value of `VAR`, or `default` if it's unset or empty
+//! - `${file:/path}`: contents of the file at `/path` (without a trailing
+//!   newline)
+//! - `$$`: a literal `$`
+
+use super::{diagnostic, Diagnostic};
+use crate::fs;
+use std::env;
+use toml::value::{Table, Value};
+
+/// Prefix of references to files
+const FILE_PREFIX: &str = "file:";
+
+/// Error interpolating a configuration value.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Error {
+    /// Key path of the value containing the reference
+    pub key_path: Vec<String>,
+
+    /// Description of the error
+    pub message: String,
+}
+
+impl Error {
+    /// Convert this error into a [`Diagnostic`], locating the offending value
+    /// in the given TOML text (if any).
+    pub fn to_diagnostic(&self, toml: Option<&str>) -> Diagnostic {
+        let diagnostic = Diagnostic::new(&self.message).with_key(self.key_path.join("."));
+
+        match toml.and_then(|text| Some((text, diagnostic::locate(text, &self.key_path)?))) {
+            Some((text, (line, column))) => diagnostic.with_location(text, line, column),
+            None => diagnostic,
+        }
+    }
+}
+
+/// Expand all references in the string values of the given table (including
+/// those nested in tables and arrays).
+pub(crate) fn interpolate(table: &mut Table) -> Result<(), Error> {
+    let mut key_path = vec![];
+    interpolate_table(table, &mut key_path)
+}
+
+/// Expand all references in a table with the given key path.
+fn interpolate_table(table: &mut Table, key_path: &mut Vec<String>) -> Result<(), Error> {
+    for (key, value) in table.iter_mut() {
+        key_path.push(key.clone());
+        interpolate_value(value, key_path)?;
+        key_path.pop();
+    }
+
+    Ok(())
+}
+
+/// Expand all references in a value with the given key path.
+fn interpolate_value(value: &mut Value, key_path: &mut Vec<String>) -> Result<(), Error> {
+    match value {
+        Value::String(s) => {
+            *s = expand(s).map_err(|message| Error {
+                key_path: key_path.clone(),
+                message,
+            })?;
+        }
+        Value::Array(values) => {
+            for value in values {
+                interpolate_value(value, key_path)?;
+            }
+        }
+        Value::Table(table) => interpolate_table(table, key_path)?,
+        _ => (),
+    }
+
+    Ok(())
+}
+
+/// Expand all references in the given string.
+fn expand(s: &str) -> Result<String, String> {
+    let mut result = String::with_capacity(s.len());
+    let mut rest = s;
+
+    while let Some(pos) = rest.find('$') {
+        result.push_str(&rest[..pos]);
+        rest = &rest[pos..];
+
+        if let Some(after) = rest.strip_prefix("$$") {
+            result.push('$');
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("${") {
+            let end = after
+                .find('}')
+                .ok_or_else(|| format!("unterminated reference in {:?}", s))?;
+
+            result.push_str(&resolve(&after[..end])?);
+            rest = &after[(end + 1)..];
+        } else {
+            result.push('$');
+            rest = &rest[1..];
+        }
+    }
+
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// Resolve the given reference (i.e. the contents of `${...}`).
+fn resolve(reference: &str) -> Result<String, String> {
+    if let Some(path) = reference.strip_prefix(FILE_PREFIX) {
+        let mut contents = fs::read_to_string(path).map_err(|e| e.to_string())?;
+
+        if contents.ends_with('\n') {
+            contents.pop();
+
+            if contents.ends_with('\r') {
+                contents.pop();
+            }
+        }
+
+        return Ok(contents);
+    }
+
+    let (name, default) = match reference.find(":-") {
+        Some(pos) => (&reference[..pos], Some(&reference[(pos + 2)..])),
+        None => (reference, None),
+    };
+
+    if name.is_empty() {
+        return Err(format!("empty reference `${{{}}}`", reference));
+    }
+
+    match (env::var(name), default) {
+        (Ok(value), Some(default)) if value.is_empty() => Ok(default.to_owned()),
+        (Ok(value), _) => Ok(value),
+        (Err(env::VarError::NotPresent), Some(default)) => Ok(default.to_owned()),
+        (Err(env::VarError::NotPresent), None) => {
+            Err(format!("environment variable {} is not set", name))
+        }
+        (Err(env::VarError::NotUnicode(_)), _) => {
+            Err(format!("environment variable {} is not valid UTF-8", name))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expand_references() {
+        env::set_var("ABSCISSA_INTERPOLATE_TEST", "value");
+        env::set_var("ABSCISSA_INTERPOLATE_EMPTY", "");
+
+        assert_eq!(
+            expand("a ${ABSCISSA_INTERPOLATE_TEST} b").unwrap(),
+            "a value b"
+        );
+        assert_eq!(
+            expand("${ABSCISSA_INTERPOLATE_MISSING:-default}").unwrap(),
+            "default"
+        );
+        assert_eq!(
+            expand("${ABSCISSA_INTERPOLATE_EMPTY:-default}").unwrap(),
+            "default"
+        );
+        assert_eq!(
+            expand("$$ ${ABSCISSA_INTERPOLATE_TEST} $5").unwrap(),
+            "$ value $5"
+        );
+        assert_eq!(
+            expand("$${ABSCISSA_INTERPOLATE_TEST}").unwrap(),
+            "${ABSCISSA_INTERPOLATE_TEST}"
+        );
+    }
+
+    #[test]
+    fn expand_file() {
+        let path = env::temp_dir().join(format!("abscissa-interpolate-{}", std::process::id()));
+        fs::write(&path, "secret\n").unwrap();
+
+        assert_eq!(
+            expand(&format!("${{file:{}}}", path.display())).unwrap(),
+            "secret"
+        );
+
+        fs::remove_file(&path).unwrap();
+        let err = expand(&format!("${{file:{}}}", path.display())).unwrap_err();
+        assert!(err.contains(&path.display().to_string()));
+    }
+
+    #[test]
+    fn unresolved_reference() {
+        let mut table: Table =
+            toml::from_str("[database]\nhosts = [\"${ABSCISSA_INTERPOLATE_UNSET}\"]").unwrap();
+
+        let err = interpolate(&mut table).unwrap_err();
+        assert_eq!(err.key_path, ["database", "hosts"]);
+        assert_eq!(
+            err.message,
+            "environment variable ABSCISSA_INTERPOLATE_UNSET is not set"
+        );
+    }
+}
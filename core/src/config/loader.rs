@@ -0,0 +1,1081 @@
+//! Layered configuration loader
+
+use super::{
+    assignment::Assignment,
+    diagnostic::{self, Diagnostic},
+    env::EnvVar,
+    interpolate,
+    migration::Migrations,
+    profile,
+    provenance::{Origin, Provenance},
+    source::STDIN_NAME,
+    unknown_keys::{self, UnknownKey, UnknownKeys},
+    Config, Format, Source,
+};
+use crate::{
+    path::AbsPathBuf,
+    FrameworkError,
+    FrameworkErrorKind::{ConfigError, PathError},
+};
+use serde_path_to_error::{Path, Segment};
+use std::{io, path::PathBuf};
+use toml::value::{Table, Value};
+
+/// Layered configuration loader.
+///
+/// Loads tables from an ordered list of [`Source`]s and deep-merges
+/// them, with later sources taking precedence over earlier ones, before
+/// deserializing the result into the application's configuration type.
+///
+/// Each source is parsed according to the [`Format`] indicated by its file
+/// extension, so e.g. a YAML file may be layered on top of a TOML file.
+///
+/// Optionally, [profiles][`super::profile`] are enabled (merging the selected
+/// one over the result), and environment variables with a given prefix are overlaid on top of the
+/// sources (see the [`env`][`super::env`] module). Finally, command-line
+/// [assignments][`super::assignment`] are applied.
+#[derive(Clone, Debug, Default)]
+pub struct Loader {
+    /// Configuration sources, in order of increasing precedence
+    sources: Vec<Source>,
+
+    /// Prefix of environment variables to overlay onto the sources
+    env_prefix: Option<String>,
+
+    /// Migrations to apply to outdated sources
+    migrations: Migrations,
+
+    /// Expand references inside string values of sources?
+    interpolate: bool,
+
+    /// Handle the `profile` table of the sources?
+    profiles: bool,
+
+    /// Profile to merge over the sources
+    profile: Option<String>,
+
+    /// Assignments to apply after all other sources
+    assignments: Vec<Assignment>,
+
+    /// How to handle unknown keys (if not left to the configuration type)
+    unknown_keys: Option<UnknownKeys>,
+}
+
+impl Loader {
+    /// Create a new loader with no sources.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a source which takes precedence over all previously added sources.
+    pub fn source(&mut self, source: Source) -> &mut Self {
+        self.sources.push(source);
+        self
+    }
+
+    /// Remove all previously added sources.
+    pub fn clear_sources(&mut self) -> &mut Self {
+        self.sources.clear();
+        self
+    }
+
+    /// Add multiple sources, in order of increasing precedence.
+    pub fn sources<I>(&mut self, sources: I) -> &mut Self
+    where
+        I: IntoIterator<Item = Source>,
+    {
+        self.sources.extend(sources);
+        self
+    }
+
+    /// Overlay environment variables with the given prefix, e.g. with a
+    /// prefix of `MYAPP` the variable `MYAPP__DATABASE__URL` sets the
+    /// `database.url` key.
+    ///
+    /// Values are coerced to the type of the value they replace. If the key
+    /// isn't present in any source, the type is inferred from the target
+    /// type when deserializing.
+    pub fn env_prefix(&mut self, prefix: impl Into<String>) -> &mut Self {
+        self.env_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Migrate sources with an outdated `version` to the latest version
+    /// using the given migrations before merging them.
+    ///
+    /// A deprecation notice is printed for each migration applied.
+    pub fn migrations(&mut self, migrations: Migrations) -> &mut Self {
+        self.migrations = migrations;
+        self
+    }
+
+    /// Expand `${VAR}`, `${VAR:-default}` and `${file:/path}` references
+    /// inside string values of the sources (with `$$` as an escape for `$`).
+    ///
+    /// Values set by environment variables are not interpolated.
+    pub fn interpolate(&mut self, enabled: bool) -> &mut Self {
+        self.interpolate = enabled;
+        self
+    }
+
+    /// Treat the top-level `profile` table of the merged sources as a table
+    /// of profiles, removing it before deserializing them (whether or not a
+    /// profile is selected).
+    ///
+    /// Profiles are disabled by default, leaving any `profile` key to the
+    /// configuration type, unless one is selected with `profile`.
+    pub fn profiles(&mut self, enabled: bool) -> &mut Self {
+        self.profiles = enabled;
+        self
+    }
+
+    /// Merge the `[profile.<name>]` table of the merged sources over the
+    /// rest of them, before environment variables are applied.
+    ///
+    /// Selecting a profile enables `profiles`. Selecting one which isn't
+    /// defined by any of the loaded sources is an error.
+    pub fn profile(&mut self, profile: Option<String>) -> &mut Self {
+        if profile.is_some() {
+            self.profiles = true;
+        }
+
+        self.profile = profile;
+        self
+    }
+
+    /// Get the profile selected with `profile`, if any.
+    pub(crate) fn selected_profile(&self) -> Option<&str> {
+        self.profile.as_deref()
+    }
+
+    /// Apply the given assignments (e.g. from `--set key=value`) after all
+    /// other sources, including environment variables.
+    pub fn assignments<I>(&mut self, assignments: I) -> &mut Self
+    where
+        I: IntoIterator<Item = Assignment>,
+    {
+        self.assignments.extend(assignments);
+        self
+    }
+
+    /// Handle keys which aren't fields of the configuration type in the given
+    /// way, rather than leaving it to the type (see the
+    /// [`unknown_keys`][`super::unknown_keys`] module).
+    pub fn unknown_keys(&mut self, mode: UnknownKeys) -> &mut Self {
+        self.unknown_keys = Some(mode);
+        self
+    }
+
+    /// Load and merge all sources into a single TOML table.
+    ///
+    /// Missing optional sources are skipped, whereas a missing required
+    /// source results in a `PathError`. Returns `None` if no sources
+    /// were loaded.
+    pub fn load_table(&self) -> Result<Option<Table>, FrameworkError> {
+        let (layers, mut env_vars) = self.load_layers()?;
+
+        if layers.is_empty() && env_vars.is_empty() && self.assignments.is_empty() {
+            return Ok(None);
+        }
+
+        let mut table = Table::new();
+
+        for layer in layers {
+            merge(&mut table, layer.table);
+        }
+
+        if self.profiles {
+            profile::apply(&mut table, self.profile.as_deref())?;
+        }
+
+        for var in &mut env_vars {
+            var.apply(&mut table)?;
+        }
+
+        for assignment in &self.assignments {
+            assignment.apply(&mut table)?;
+        }
+
+        Ok(Some(table))
+    }
+
+    /// Load and merge all sources, deserializing the result.
+    ///
+    /// Uses the default configuration if no sources were loaded.
+    pub fn load<C: Config>(&self) -> Result<C, FrameworkError> {
+        self.load_with_provenance().map(|(config, _)| config)
+    }
+
+    /// Load and merge all sources, deserializing the result and recording
+    /// the [`Provenance`] of each value.
+    ///
+    /// Values are attributed to the last source which set them, and then to
+    /// the environment variables and assignments which override them.
+    pub fn load_with_provenance<C: Config>(&self) -> Result<(C, Provenance), FrameworkError> {
+        self.load_with_table()
+            .map(|(config, provenance, _)| (config, provenance))
+    }
+
+    /// Load and merge all sources as with `load_with_provenance`, also
+    /// returning the merged table the configuration was deserialized from
+    /// (if any sources were loaded).
+    pub(crate) fn load_with_table<C: Config>(
+        &self,
+    ) -> Result<(C, Provenance, Option<Table>), FrameworkError> {
+        let (layers, mut env_vars) = self.load_layers()?;
+        let mut provenance = Provenance::new();
+
+        if layers.is_empty() && env_vars.is_empty() && self.assignments.is_empty() {
+            return Ok((C::default(), provenance, None));
+        }
+
+        let mut table = Table::new();
+
+        for layer in &layers {
+            merge(&mut table, layer.table.clone());
+            provenance.record_table(&layer.table, |key_path| layer.origin(key_path));
+        }
+
+        if self.profiles {
+            profile::apply(&mut table, self.profile.as_deref())?;
+            provenance.apply_profile(self.profile.as_deref());
+        }
+
+        for var in &mut env_vars {
+            var.apply(&mut table)?;
+
+            let origin = Origin::Env {
+                var: var.name.clone(),
+            };
+            provenance.record(var.path.join("."), origin);
+        }
+
+        for assignment in &self.assignments {
+            assignment.apply(&mut table)?;
+            provenance.record(assignment.key_path().join("."), Origin::Cli);
+        }
+
+        loop {
+            let result = match self.unknown_keys {
+                Some(_) => unknown_keys::deserialize(table.clone()),
+                None => serde_path_to_error::deserialize(Value::Table(table.clone()))
+                    .map(|config| (config, vec![])),
+            };
+
+            let err = match result {
+                Ok((config, unknown)) => {
+                    self.handle_unknown_keys(&layers, &env_vars, &unknown)?;
+                    return Ok((config, provenance, Some(table)));
+                }
+                Err(err) => err,
+            };
+
+            let key_path = map_keys(err.path());
+            let mut message = err.inner().to_string();
+            diagnostic::strip_key(&mut message);
+
+            let mut diagnostic = Diagnostic::new(&message);
+
+            if !key_path.is_empty() {
+                diagnostic = diagnostic.with_key(err.path());
+            }
+
+            if let Some(assignment) = self
+                .assignments
+                .iter()
+                .rev()
+                .find(|a| key_path.starts_with(a.key_path()))
+            {
+                let message = format!("--set {}: {}", assignment, message);
+                diagnostic = Diagnostic::new(message).with_key(err.path());
+            } else if let Some(var) = env_vars
+                .iter_mut()
+                .find(|var| key_path.starts_with(&var.path))
+            {
+                // Retry values whose inferred type didn't match as strings
+                if var.inferred {
+                    var.apply_as_str(&mut table);
+                    continue;
+                }
+
+                let message = format!("invalid value in {}: {}", var.name, message);
+                diagnostic = Diagnostic::new(message).with_key(err.path());
+            } else {
+                diagnostic = self.with_source_location(diagnostic, &layers, &key_path);
+            }
+
+            return Err(ConfigError.context(diagnostic).into());
+        }
+    }
+
+    /// Reject or report the given unknown keys according to the selected
+    /// [`UnknownKeys`] mode.
+    fn handle_unknown_keys(
+        &self,
+        layers: &[Layer],
+        env_vars: &[EnvVar],
+        unknown: &[UnknownKey],
+    ) -> Result<(), FrameworkError> {
+        for key in unknown {
+            let key_path = &key.key_path;
+
+            let diagnostic = if let Some(assignment) = self
+                .assignments
+                .iter()
+                .rev()
+                .find(|a| key_path.starts_with(a.key_path()))
+            {
+                Diagnostic::new(format!("--set {}: {}", assignment, key))
+            } else if let Some(var) = env_vars.iter().find(|var| key_path.starts_with(&var.path)) {
+                Diagnostic::new(format!("{}: {}", var.name, key))
+            } else {
+                self.with_source_location(Diagnostic::new(key.to_string()), layers, key_path)
+            };
+
+            match self.unknown_keys {
+                Some(UnknownKeys::Strict) => return Err(ConfigError.context(diagnostic).into()),
+                Some(UnknownKeys::Warn) => super::notice(diagnostic.to_string()),
+                Some(UnknownKeys::Silent) | None => (),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Annotate the given diagnostic with the path to the source which set
+    /// the value at the given key path, and its location within it (if
+    /// known).
+    fn with_source_location(
+        &self,
+        mut diagnostic: Diagnostic,
+        layers: &[Layer],
+        key_path: &[String],
+    ) -> Diagnostic {
+        if let Some((layer, source_path)) = self.locate_source(layers, key_path) {
+            diagnostic = diagnostic.with_path(&layer.path);
+
+            if layer.format == Format::Toml && !layer.migrated {
+                if let Some((line, column)) = diagnostic::locate(&layer.text, &source_path) {
+                    diagnostic = diagnostic.with_location(&layer.text, line, column);
+                }
+            }
+        }
+
+        diagnostic
+    }
+
+    /// Find the layer which set the value at the given key path, along with
+    /// the key path of the value within it (i.e. inside the selected profile
+    /// if the profile set it).
+    fn locate_source<'a>(
+        &self,
+        layers: &'a [Layer],
+        key_path: &[String],
+    ) -> Option<(&'a Layer, Vec<String>)> {
+        if key_path.is_empty() {
+            return None;
+        }
+
+        if let Some(name) = &self.profile {
+            let mut profile_path = vec![profile::PROFILE_KEY.to_owned(), name.clone()];
+            profile_path.extend_from_slice(key_path);
+
+            if let Some(layer) = layers.iter().rev().find(|l| l.contains(&profile_path)) {
+                return Some((layer, profile_path));
+            }
+        }
+
+        layers
+            .iter()
+            .rev()
+            .find(|layer| layer.contains(key_path))
+            .map(|layer| (layer, key_path.to_vec()))
+    }
+
+    /// Load all sources which are present, along with the environment
+    /// variables to overlay on top of them.
+    fn load_layers(&self) -> Result<(Vec<Layer>, Vec<EnvVar>), FrameworkError> {
+        let mut layers = vec![];
+
+        for source in &self.sources {
+            if let Some(mut layer) = Layer::load(source)? {
+                if !self.migrations.is_empty() {
+                    layer.migrate(&self.migrations)?;
+                }
+
+                if self.interpolate {
+                    layer.interpolate()?;
+                }
+
+                layers.push(layer);
+            }
+        }
+
+        let env_vars = match &self.env_prefix {
+            Some(prefix) => EnvVar::collect(prefix)?,
+            None => vec![],
+        };
+
+        Ok((layers, env_vars))
+    }
+}
+
+/// Configuration file loaded from a [`Source`].
+struct Layer {
+    /// Canonical path to the file (or `<stdin>`)
+    path: PathBuf,
+
+    /// Format of the file
+    format: Format,
+
+    /// Text of the file
+    text: String,
+
+    /// Parsed table
+    table: Table,
+
+    /// Has the table been migrated (i.e. does it no longer match the text)?
+    migrated: bool,
+}
+
+impl Layer {
+    /// Load the layer for the given source, returning `None` if it's
+    /// optional and missing.
+    fn load(source: &Source) -> Result<Option<Self>, FrameworkError> {
+        if source.is_stdin() {
+            let text = super::source::read_stdin()?;
+            let path = PathBuf::from(STDIN_NAME);
+            let table = Format::Toml.parse(text, Some(&path))?;
+
+            return Ok(Some(Self {
+                path,
+                format: Format::Toml,
+                text: text.to_owned(),
+                table,
+                migrated: false,
+            }));
+        }
+
+        let path = match AbsPathBuf::canonicalize(source.path()) {
+            Ok(path) => path,
+            Err(e) if e.kind() == io::ErrorKind::NotFound && !source.is_required() => {
+                return Ok(None);
+            }
+            Err(_) => {
+                let path_error = PathError {
+                    name: Some(source.path().into()),
+                };
+                return Err(ConfigError.context(path_error).into());
+            }
+        };
+
+        let format = Format::from_path(path.as_path())?;
+        let text = super::read_file(path.as_ref())?;
+        let table = format.parse_table(&text, Some(path.as_path()))?;
+
+        Ok(Some(Self {
+            path: path.as_path().to_owned(),
+            format,
+            text,
+            table,
+            migrated: false,
+        }))
+    }
+
+    /// Migrate this layer to the latest version.
+    fn migrate(&mut self, migrations: &Migrations) -> Result<(), FrameworkError> {
+        let table = std::mem::take(&mut self.table);
+
+        let (table, applied) = migrations
+            .migrate(table)
+            .map_err(|e| format_err!(ConfigError, "{}: {}", self.path.display(), e))?;
+
+        for migration in applied {
+            super::notice(format!(
+                "{}: migrated config from deprecated version {} to {}: {}",
+                self.path.display(),
+                migration.from_version(),
+                migration.to_version(),
+                migration.description()
+            ));
+        }
+
+        self.table = table;
+        self.migrated = !applied.is_empty();
+        Ok(())
+    }
+
+    /// Expand references inside the string values of this layer.
+    fn interpolate(&mut self) -> Result<(), FrameworkError> {
+        interpolate::interpolate(&mut self.table).map_err(|e| {
+            let text = if self.format == Format::Toml && !self.migrated {
+                Some(self.text.as_str())
+            } else {
+                None
+            };
+
+            let diagnostic = e.to_diagnostic(text).with_path(&self.path);
+            ConfigError.context(diagnostic).into()
+        })
+    }
+
+    /// Get the origin of the value at the given key path in this layer.
+    fn origin(&self, key_path: &[String]) -> Origin {
+        let line = if self.format == Format::Toml && !self.migrated {
+            diagnostic::locate(&self.text, key_path).map(|(line, _)| line + 1)
+        } else {
+            None
+        };
+
+        Origin::File {
+            path: self.path.clone(),
+            line,
+        }
+    }
+
+    /// Does this layer contain a value for the given key path?
+    fn contains(&self, key_path: &[String]) -> bool {
+        let mut table = &self.table;
+
+        for (i, key) in key_path.iter().enumerate() {
+            match table.get(key) {
+                Some(Value::Table(t)) => table = t,
+                Some(_) => return i == key_path.len() - 1,
+                None => return false,
+            }
+        }
+
+        true
+    }
+}
+
+/// Deep-merge the `overlay` table into `base`.
+///
+/// Nested tables are merged recursively. All other values (including arrays)
+/// in `overlay` replace the corresponding values in `base`.
+pub fn merge(base: &mut Table, overlay: Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Table(base_table)), Value::Table(overlay_table)) => {
+                merge(base_table, overlay_table)
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Get the leading map keys in a deserialization error path.
+pub(super) fn map_keys(path: &Path) -> Vec<String> {
+    let mut keys = vec![];
+
+    for segment in path.iter() {
+        match segment {
+            Segment::Map { key } => keys.push(key.clone()),
+            _ => break,
+        }
+    }
+
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs;
+    use serde::Deserialize;
+    use std::{env, path::PathBuf};
+
+    #[derive(Debug, Default, Deserialize)]
+    struct ExampleConfig {
+        name: String,
+        port: u16,
+    }
+
+    /// Write a config file to a temporary location for a test
+    fn write_config(filename: &str, contents: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!("abscissa-{}-{}", std::process::id(), filename));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn merge_nested_tables() {
+        let mut base: Table = toml::from_str(
+            r#"
+            name = "base"
+            list = [1, 2]
+
+            [server]
+            host = "localhost"
+            port = 8080
+            "#,
+        )
+        .unwrap();
+
+        let overlay: Table = toml::from_str(
+            r#"
+            list = [3]
+
+            [server]
+            port = 9090
+            "#,
+        )
+        .unwrap();
+
+        merge(&mut base, overlay);
+
+        let expected: Table = toml::from_str(
+            r#"
+            name = "base"
+            list = [3]
+
+            [server]
+            host = "localhost"
+            port = 9090
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(base, expected);
+    }
+
+    #[test]
+    fn missing_sources() {
+        let path = "/nonexistent/abscissa-config.toml";
+
+        let table = Loader::new()
+            .source(Source::optional(path))
+            .load_table()
+            .unwrap();
+        assert!(table.is_none());
+
+        let err = Loader::new()
+            .source(Source::required(path))
+            .load_table()
+            .unwrap_err();
+        assert_eq!(*err.kind(), ConfigError);
+    }
+
+    #[test]
+    fn layered_sources() {
+        let system = write_config("system.toml", "name = \"system\"\nport = 80\n");
+        let user = write_config("user.toml", "port = 8080\n");
+
+        let config: ExampleConfig = Loader::new()
+            .sources(vec![
+                Source::required(&system),
+                Source::optional("/nonexistent/abscissa-config.toml"),
+                Source::optional(&user),
+            ])
+            .load()
+            .unwrap();
+
+        assert_eq!(config.name, "system");
+        assert_eq!(config.port, 8080);
+
+        fs::remove_file(system).unwrap();
+        fs::remove_file(user).unwrap();
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn mixed_formats() {
+        let base = write_config("mixed-base.toml", "name = \"base\"\nport = 80\n");
+        let json = write_config("mixed-overlay.json", "{ \"port\": 8080 }");
+
+        let config: ExampleConfig = Loader::new()
+            .sources(vec![Source::required(&base), Source::required(&json)])
+            .load()
+            .unwrap();
+
+        assert_eq!(config.name, "base");
+        assert_eq!(config.port, 8080);
+
+        fs::remove_file(base).unwrap();
+        fs::remove_file(json).unwrap();
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_nulls() {
+        #[derive(Debug, Default, Deserialize)]
+        struct NullableConfig {
+            name: Option<String>,
+            port: u16,
+        }
+
+        let path = write_config("nulls.json", "{ \"name\": null, \"port\": 8080 }");
+
+        let config: NullableConfig = Loader::new()
+            .source(Source::required(&path))
+            .load()
+            .unwrap();
+
+        assert_eq!(config.name, None);
+        assert_eq!(config.port, 8080);
+
+        let config = NullableConfig::load_file(AbsPathBuf::canonicalize(&path).unwrap()).unwrap();
+        assert_eq!(config.name, None);
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_syntax_diagnostic() {
+        let path = write_config("syntax.json", "{\n  \"port\": 80,\n}");
+
+        let err = Loader::new()
+            .source(Source::required(&path))
+            .load::<ExampleConfig>()
+            .unwrap_err();
+
+        let diagnostic = std::error::Error::source(&err)
+            .and_then(|e| e.downcast_ref::<Diagnostic>())
+            .unwrap();
+
+        assert_eq!(diagnostic.message(), "trailing comma");
+        assert_eq!(diagnostic.line_col(), Some((3, 1)));
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn env_overlay() {
+        let path = write_config("env.toml", "name = \"file\"\nport = 80\n");
+        env::set_var("ABSCISSA_ENV_TEST__PORT", "8080");
+
+        let config: ExampleConfig = Loader::new()
+            .source(Source::required(&path))
+            .env_prefix("ABSCISSA_ENV_TEST")
+            .load()
+            .unwrap();
+
+        assert_eq!(config.name, "file");
+        assert_eq!(config.port, 8080);
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn provenance() {
+        let path = write_config("provenance.toml", "# example\nname = \"file\"\nport = 80\n");
+        env::set_var("ABSCISSA_PROVENANCE_TEST__PORT", "8080");
+
+        let (_, provenance) = Loader::new()
+            .source(Source::required(&path))
+            .env_prefix("ABSCISSA_PROVENANCE_TEST")
+            .load_with_provenance::<ExampleConfig>()
+            .unwrap();
+
+        assert_eq!(
+            provenance.origin("name"),
+            &Origin::File {
+                path: path.canonicalize().unwrap(),
+                line: Some(2)
+            }
+        );
+        assert_eq!(
+            provenance.origin("port"),
+            &Origin::Env {
+                var: "ABSCISSA_PROVENANCE_TEST__PORT".to_owned()
+            }
+        );
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn migrated_sources() {
+        let old = write_config("migrate-old.toml", "title = \"old\"\nport = 80\n");
+        let new = write_config("migrate-new.toml", "version = 2\nport = 8080\n");
+
+        let mut migrations = Migrations::new();
+        migrations.add("rename `title` to `name`", |mut value| {
+            let table = value.as_table_mut().unwrap();
+
+            if let Some(title) = table.remove("title") {
+                table.insert("name".to_owned(), title);
+            }
+
+            Ok(value)
+        });
+
+        let table = Loader::new()
+            .sources(vec![Source::required(&old), Source::required(&new)])
+            .migrations(migrations)
+            .load_table()
+            .unwrap()
+            .unwrap();
+
+        let expected: Table = toml::from_str("name = \"old\"\nport = 8080\nversion = 2\n").unwrap();
+        assert_eq!(table, expected);
+
+        fs::remove_file(old).unwrap();
+        fs::remove_file(new).unwrap();
+    }
+
+    #[test]
+    fn interpolated_sources() {
+        let path = write_config(
+            "interpolate.toml",
+            "name = \"${ABSCISSA_INTERPOLATE_NAME}\"\nport = 80\n",
+        );
+
+        let err = Loader::new()
+            .source(Source::required(&path))
+            .interpolate(true)
+            .load::<ExampleConfig>()
+            .unwrap_err();
+
+        let diagnostic = std::error::Error::source(&err)
+            .and_then(|e| e.downcast_ref::<Diagnostic>())
+            .unwrap();
+
+        assert_eq!(diagnostic.key(), Some("name"));
+        assert_eq!(diagnostic.line_col(), Some((1, 8)));
+
+        env::set_var("ABSCISSA_INTERPOLATE_NAME", "interpolated");
+
+        let config: ExampleConfig = Loader::new()
+            .source(Source::required(&path))
+            .interpolate(true)
+            .load()
+            .unwrap();
+
+        assert_eq!(config.name, "interpolated");
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn profiles() {
+        let path = write_config(
+            "profiles.toml",
+            "name = \"base\"\nport = 80\n\n[profile.dev]\nport = 8080\n\n[profile.prod]\nport = \"http\"\n",
+        );
+
+        let (config, provenance) = Loader::new()
+            .source(Source::required(&path))
+            .profile(Some("dev".to_owned()))
+            .load_with_provenance::<ExampleConfig>()
+            .unwrap();
+
+        assert_eq!(config.name, "base");
+        assert_eq!(config.port, 8080);
+        assert_eq!(
+            provenance.origin("port"),
+            &Origin::File {
+                path: path.canonicalize().unwrap(),
+                line: Some(5)
+            }
+        );
+        assert!(provenance
+            .iter()
+            .all(|(key, _)| !key.starts_with("profile")));
+
+        let config: ExampleConfig = Loader::new()
+            .source(Source::required(&path))
+            .load()
+            .unwrap();
+
+        assert_eq!(config.port, 80);
+
+        let err = Loader::new()
+            .source(Source::required(&path))
+            .profile(Some("prod".to_owned()))
+            .load::<ExampleConfig>()
+            .unwrap_err();
+
+        let diagnostic = std::error::Error::source(&err)
+            .and_then(|e| e.downcast_ref::<Diagnostic>())
+            .unwrap();
+
+        assert_eq!(diagnostic.line_col(), Some((8, 8)));
+
+        let err = Loader::new()
+            .source(Source::required(&path))
+            .profile(Some("staging".to_owned()))
+            .load::<ExampleConfig>()
+            .unwrap_err();
+
+        assert_eq!(*err.kind(), ConfigError);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn profile_field_without_profiles() {
+        #[derive(Debug, Default, Deserialize)]
+        struct ProfileConfig {
+            profile: Option<String>,
+        }
+
+        let path = write_config("profile_field.toml", "profile = \"x\"\n");
+
+        let (config, provenance) = Loader::new()
+            .source(Source::required(&path))
+            .load_with_provenance::<ProfileConfig>()
+            .unwrap();
+
+        assert_eq!(config.profile.as_deref(), Some("x"));
+        assert!(provenance.iter().any(|(key, _)| key == "profile"));
+
+        let err = Loader::new()
+            .source(Source::required(&path))
+            .profiles(true)
+            .load::<ProfileConfig>()
+            .unwrap_err();
+
+        assert_eq!(*err.kind(), ConfigError);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn assignments() {
+        let path = write_config("assignments.toml", "name = \"file\"\nport = 80\n");
+        env::set_var("ABSCISSA_ASSIGNMENT_TEST__PORT", "8080");
+
+        let (config, provenance) = Loader::new()
+            .source(Source::required(&path))
+            .env_prefix("ABSCISSA_ASSIGNMENT_TEST")
+            .assignments(vec!["port=9090".parse().unwrap()])
+            .load_with_provenance::<ExampleConfig>()
+            .unwrap();
+
+        assert_eq!(config.port, 9090);
+        assert_eq!(provenance.origin("port"), &Origin::Cli);
+
+        let err = Loader::new()
+            .source(Source::required(&path))
+            .assignments(vec!["port=eighty".parse().unwrap()])
+            .load::<ExampleConfig>()
+            .unwrap_err();
+
+        assert_eq!(*err.kind(), ConfigError);
+        assert!(err.to_string().contains("--set port=eighty"));
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn unknown_assignment() {
+        #[derive(Debug, Default, Deserialize)]
+        #[serde(deny_unknown_fields)]
+        #[allow(dead_code)]
+        struct StrictConfig {
+            #[serde(default)]
+            server: ServerConfig,
+        }
+
+        #[derive(Debug, Default, Deserialize)]
+        #[serde(deny_unknown_fields)]
+        #[allow(dead_code)]
+        struct ServerConfig {
+            port: u16,
+        }
+
+        let err = Loader::new()
+            .assignments(vec!["server.prot=80".parse().unwrap()])
+            .load::<StrictConfig>()
+            .unwrap_err();
+
+        assert_eq!(*err.kind(), ConfigError);
+        assert!(err
+            .to_string()
+            .contains("--set server.prot=80: unknown field `prot`"));
+    }
+
+    #[test]
+    fn unknown_keys() {
+        #[derive(Debug, Default, Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct StrictConfig {
+            name: String,
+        }
+
+        let path = write_config(
+            "unknown.toml",
+            "# example\nname = \"file\"\nnmae = \"typo\"\n",
+        );
+        let mut loader = Loader::new();
+        loader.source(Source::required(&path));
+
+        assert!(loader.load::<StrictConfig>().is_err());
+
+        let config: StrictConfig = loader.unknown_keys(UnknownKeys::Warn).load().unwrap();
+        assert_eq!(config.name, "file");
+
+        let err = loader
+            .unknown_keys(UnknownKeys::Strict)
+            .load::<StrictConfig>()
+            .unwrap_err();
+
+        let diagnostic = std::error::Error::source(&err)
+            .and_then(|e| e.downcast_ref::<Diagnostic>())
+            .unwrap();
+
+        assert_eq!(
+            diagnostic.message(),
+            "unknown config key `nmae` (did you mean `name`?)"
+        );
+        assert_eq!(diagnostic.line_col(), Some((3, 8)));
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn env_overlay_inferred_types() {
+        env::set_var("ABSCISSA_INFER_TEST__NAME", "123");
+        env::set_var("ABSCISSA_INFER_TEST__PORT", "8080");
+
+        let config: ExampleConfig = Loader::new()
+            .env_prefix("ABSCISSA_INFER_TEST")
+            .load()
+            .unwrap();
+
+        assert_eq!(config.name, "123");
+        assert_eq!(config.port, 8080);
+    }
+
+    #[test]
+    fn env_overlay_bad_value() {
+        env::set_var("ABSCISSA_BAD_TEST__NAME", "example");
+        env::set_var("ABSCISSA_BAD_TEST__PORT", "many");
+
+        let err = Loader::new()
+            .env_prefix("ABSCISSA_BAD_TEST")
+            .load::<ExampleConfig>()
+            .unwrap_err();
+
+        assert_eq!(*err.kind(), ConfigError);
+        assert!(err.to_string().contains("ABSCISSA_BAD_TEST__PORT"));
+    }
+
+    #[test]
+    fn type_error_diagnostic() {
+        let base = write_config("type-error-base.toml", "name = \"base\"\nport = 80\n");
+        let bad = write_config("type-error-bad.toml", "# bad port\nport = \"eighty\"\n");
+
+        let err = Loader::new()
+            .sources(vec![Source::required(&base), Source::required(&bad)])
+            .load::<ExampleConfig>()
+            .unwrap_err();
+
+        assert_eq!(*err.kind(), ConfigError);
+
+        let diagnostic = std::error::Error::source(&err)
+            .and_then(|e| e.downcast_ref::<Diagnostic>())
+            .unwrap();
+
+        assert_eq!(
+            diagnostic.path(),
+            Some(bad.canonicalize().unwrap().as_path())
+        );
+        assert_eq!(diagnostic.key(), Some("port"));
+        assert_eq!(diagnostic.line_col(), Some((2, 8)));
+
+        fs::remove_file(base).unwrap();
+        fs::remove_file(bad).unwrap();
+    }
+}
@@ -0,0 +1,235 @@
+//! Versioned configuration with migrations between versions.
+//!
+//! Configuration files declare the version of their schema with a top-level
+//! `version = N` key. Files without one are version 1. When the schema
+//! changes, register a [`Migrations`] step which transforms the raw TOML
+//! value of a file from the previous version into the new one:
+//!
+//! ```
+//! use abscissa_core::config::Migrations;
+//!
+//! let mut migrations = Migrations::new();
+//!
+//! // Version 1 => 2: `name` was renamed to `hello.recipient`
+//! migrations.add("rename `name` to `hello.recipient`", |mut value| {
+//!     if let Some(table) = value.as_table_mut() {
+//!         if let Some(name) = table.remove("name") {
+//!             let mut hello = toml::value::Table::new();
+//!             hello.insert("recipient".to_owned(), name);
+//!             table.insert("hello".to_owned(), hello.into());
+//!         }
+//!     }
+//!     Ok(value)
+//! });
+//!
+//! assert_eq!(migrations.latest_version(), 2);
+//! ```
+//!
+//! The application's configuration type should accept the `version` key,
+//! e.g. with a `version: u32` field which defaults to the latest version.
+
+use crate::{FrameworkError, FrameworkErrorKind::ConfigError};
+use std::{fmt, sync::Arc};
+use toml::value::{Table, Value};
+
+/// Name of the key containing the version of a configuration file
+pub const VERSION_KEY: &str = "version";
+
+/// Version of configuration files which don't have a `version` key
+pub const INITIAL_VERSION: u32 = 1;
+
+/// Migration step function
+type MigrationFn = dyn Fn(Value) -> Result<Value, FrameworkError> + Send + Sync;
+
+/// Migration from one version of the configuration to the next.
+#[derive(Clone)]
+pub struct Migration {
+    /// Version this migration upgrades from
+    from_version: u32,
+
+    /// Description of the changes made by this migration
+    description: String,
+
+    /// Function which performs the migration
+    function: Arc<MigrationFn>,
+}
+
+impl Migration {
+    /// Get the version this migration upgrades from.
+    pub fn from_version(&self) -> u32 {
+        self.from_version
+    }
+
+    /// Get the version this migration upgrades to.
+    pub fn to_version(&self) -> u32 {
+        self.from_version + 1
+    }
+
+    /// Get the description of the changes made by this migration.
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+}
+
+impl fmt::Debug for Migration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Migration")
+            .field("from_version", &self.from_version)
+            .field("description", &self.description)
+            .finish()
+    }
+}
+
+/// Registry of the migrations between successive configuration versions.
+#[derive(Clone, Debug, Default)]
+pub struct Migrations {
+    /// Migrations in order, starting from [`INITIAL_VERSION`]
+    steps: Vec<Migration>,
+}
+
+impl Migrations {
+    /// Create an empty registry, i.e. the configuration is at its initial
+    /// version.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a migration from the current latest version to the next one.
+    pub fn add<F>(&mut self, description: impl Into<String>, function: F) -> &mut Self
+    where
+        F: Fn(Value) -> Result<Value, FrameworkError> + Send + Sync + 'static,
+    {
+        self.steps.push(Migration {
+            from_version: self.latest_version(),
+            description: description.into(),
+            function: Arc::new(function),
+        });
+        self
+    }
+
+    /// Get the latest configuration version.
+    pub fn latest_version(&self) -> u32 {
+        INITIAL_VERSION + self.steps.len() as u32
+    }
+
+    /// Are there any migrations in this registry?
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Migrate the given configuration table to the latest version, setting
+    /// its `version` key accordingly.
+    ///
+    /// Returns the migrated table along with the migrations which were
+    /// applied (if any).
+    pub fn migrate(&self, table: Table) -> Result<(Table, &[Migration]), FrameworkError> {
+        let version = match table.get(VERSION_KEY) {
+            None => INITIAL_VERSION,
+            Some(value) => match value.as_integer().and_then(|n| u32::try_from(n).ok()) {
+                Some(n) if n >= INITIAL_VERSION => n,
+                _ => fail!(
+                    ConfigError,
+                    "invalid value for key `{}`: {}",
+                    VERSION_KEY,
+                    value
+                ),
+            },
+        };
+
+        ensure!(
+            version <= self.latest_version(),
+            ConfigError,
+            "config version {} is newer than the latest supported version ({})",
+            version,
+            self.latest_version()
+        );
+
+        let pending = &self.steps[(version - INITIAL_VERSION) as usize..];
+        let mut value = Value::Table(table);
+
+        for migration in pending {
+            value = (migration.function)(value).map_err(|e| {
+                format_err!(
+                    ConfigError,
+                    "error migrating config from version {} to {}: {}",
+                    migration.from_version(),
+                    migration.to_version(),
+                    e
+                )
+            })?;
+        }
+
+        let mut table = match value {
+            Value::Table(table) => table,
+            _ => fail!(ConfigError, "config migration didn't produce a table"),
+        };
+
+        if !pending.is_empty() {
+            table.insert(
+                VERSION_KEY.to_owned(),
+                Value::Integer(i64::from(self.latest_version())),
+            );
+        }
+
+        Ok((table, pending))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Example migrations: 1 => 2 renames `name` to `title`, 2 => 3 removes
+    /// `legacy`
+    fn example_migrations() -> Migrations {
+        let mut migrations = Migrations::new();
+
+        migrations
+            .add("rename `name` to `title`", |mut value| {
+                let table = value.as_table_mut().unwrap();
+
+                if let Some(name) = table.remove("name") {
+                    table.insert("title".to_owned(), name);
+                }
+
+                Ok(value)
+            })
+            .add("remove `legacy`", |mut value| {
+                value.as_table_mut().unwrap().remove("legacy");
+                Ok(value)
+            });
+
+        migrations
+    }
+
+    #[test]
+    fn migrate_from_initial_version() {
+        let table: Table = toml::from_str("name = \"example\"\nlegacy = true\n").unwrap();
+        let migrations = example_migrations();
+        let (table, applied) = migrations.migrate(table).unwrap();
+
+        assert_eq!(applied.len(), 2);
+        assert_eq!(
+            table,
+            toml::from_str("title = \"example\"\nversion = 3\n").unwrap()
+        );
+    }
+
+    #[test]
+    fn migrate_partially() {
+        let table: Table = toml::from_str("version = 2\nlegacy = true\n").unwrap();
+        let migrations = example_migrations();
+        let (table, applied) = migrations.migrate(table).unwrap();
+
+        assert_eq!(applied.len(), 1);
+        assert_eq!(applied[0].description(), "remove `legacy`");
+        assert_eq!(table, toml::from_str("version = 3\n").unwrap());
+    }
+
+    #[test]
+    fn newer_version() {
+        let table: Table = toml::from_str("version = 4\n").unwrap();
+        let err = example_migrations().migrate(table).unwrap_err();
+        assert_eq!(*err.kind(), ConfigError);
+    }
+}
@@ -2,7 +2,28 @@
 
 use crate::{Command, Config, FrameworkError};
 
+pub use abscissa_derive::Override;
+
 /// Use options from the given `Command` to override settings in the config.
+///
+/// This can be derived for commands whose fields map directly onto config
+/// settings, naming the configuration type on the struct and the key path
+/// of each overridden setting on its field:
+///
+/// ```ignore
+/// #[derive(clap::Parser, Command, Debug, Override)]
+/// #[config(type = "MyConfig")]
+/// pub struct StartCmd {
+///     /// Port to listen on
+///     #[arg(long)]
+///     #[config(path = "server.port")]
+///     port: Option<u16>,
+/// }
+/// ```
+///
+/// Only values which were passed are applied: `Option<T>` fields when
+/// they're `Some`, `Vec<T>` fields when they're nonempty, and `bool` flags
+/// when they're `true`.
 pub trait Override<Cfg: Config>: Command {
     /// Process the given command line options, overriding settings from
     /// a configuration file using explicit flags taken from command-line
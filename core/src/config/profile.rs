@@ -0,0 +1,106 @@
+//! Configuration profiles: named overlays within a configuration, e.g.
+//!
+//! ```toml
+//! [server]
+//! port = 8080
+//!
+//! [profile.prod.server]
+//! port = 80
+//! ```
+//!
+//! Profiles are opt-in: see `Loader::profiles` and
+//! `Application::config_profiles`. When a profile is selected (e.g. with
+//! `--profile prod`), its table is deep-merged over the rest of the
+//! configuration before deserializing it. Once profiles are enabled, the
+//! `profile` table itself is always removed.
+//!
+//! The profile an application's configuration was loaded with is available
+//! from `State::config_profile`.
+
+use super::merge;
+use crate::{FrameworkError, FrameworkErrorKind::ConfigError};
+use toml::value::{Table, Value};
+
+/// Name of the table containing profiles
+pub const PROFILE_KEY: &str = "profile";
+
+/// Name of the environment variable which selects a profile for the
+/// application with the given environment variable prefix, e.g. `MY_APP`
+/// => `MY_APP_PROFILE`.
+pub fn env_var(prefix: &str) -> String {
+    format!("{}_PROFILE", prefix)
+}
+
+/// Remove the profiles from the given table, merging the given profile (if
+/// any) over the rest of the table.
+pub(crate) fn apply(table: &mut Table, profile: Option<&str>) -> Result<(), FrameworkError> {
+    let mut profiles = match table.remove(PROFILE_KEY) {
+        Some(Value::Table(profiles)) => profiles,
+        Some(_) => fail!(ConfigError, "`{}` is not a table", PROFILE_KEY),
+        None => Table::new(),
+    };
+
+    let name = match profile {
+        Some(name) => name,
+        None => return Ok(()),
+    };
+
+    match profiles.remove(name) {
+        Some(Value::Table(overlay)) => {
+            merge(table, overlay);
+            Ok(())
+        }
+        Some(_) => fail!(ConfigError, "`{}.{}` is not a table", PROFILE_KEY, name),
+        None => fail!(ConfigError, "unknown config profile: `{}`", name),
+    }
+}
+
+/// Command-line arguments for selecting a configuration profile.
+///
+/// Add them to the application's entry point with `#[command(flatten)]`.
+#[cfg(feature = "options")]
+#[derive(clap::Args, Clone, Debug, Default)]
+pub struct ProfileArgs {
+    /// Use the specified configuration profile
+    #[arg(long, global = true)]
+    pub profile: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE_TOML: &str = r#"
+        [server]
+        host = "localhost"
+        port = 8080
+
+        [profile.prod.server]
+        port = 80
+    "#;
+
+    #[test]
+    fn apply_profile() {
+        let mut table: Table = toml::from_str(EXAMPLE_TOML).unwrap();
+        apply(&mut table, Some("prod")).unwrap();
+
+        let expected: Table =
+            toml::from_str("[server]\nhost = \"localhost\"\nport = 80\n").unwrap();
+        assert_eq!(table, expected);
+    }
+
+    #[test]
+    fn no_profile() {
+        let mut table: Table = toml::from_str(EXAMPLE_TOML).unwrap();
+        apply(&mut table, None).unwrap();
+        assert!(!table.contains_key(PROFILE_KEY));
+        assert_eq!(table["server"]["port"].as_integer(), Some(8080));
+    }
+
+    #[test]
+    fn unknown_profile() {
+        let mut table: Table = toml::from_str(EXAMPLE_TOML).unwrap();
+        let err = apply(&mut table, Some("staging")).unwrap_err();
+        assert_eq!(*err.kind(), ConfigError);
+    }
+}
@@ -0,0 +1,244 @@
+//! Configuration provenance: where each configuration value came from.
+
+use crate::{FrameworkError, FrameworkErrorKind::ConfigError};
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+    path::PathBuf,
+};
+use toml::value::{Table, Value};
+
+/// Origin of a configuration value.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Origin {
+    /// Default value of the configuration type
+    Default,
+
+    /// Configuration file
+    File {
+        /// Path to the file
+        path: PathBuf,
+
+        /// One-based line on which the value was set (if known)
+        line: Option<usize>,
+    },
+
+    /// Environment variable
+    Env {
+        /// Name of the environment variable
+        var: String,
+    },
+
+    /// Command-line option (i.e. `Configurable::config_assignments` or
+    /// `Configurable::process_config`)
+    Cli,
+}
+
+impl Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Origin::Default => f.write_str("default value"),
+            Origin::File {
+                path,
+                line: Some(line),
+            } => write!(f, "{}:{}", path.display(), line),
+            Origin::File { path, line: None } => write!(f, "{}", path.display()),
+            Origin::Env { var } => write!(f, "environment variable {}", var),
+            Origin::Cli => f.write_str("command-line option"),
+        }
+    }
+}
+
+/// Provenance of a configuration: the [`Origin`] of the value at each
+/// (dotted) key path, e.g. `database.url`.
+///
+/// Only leaf values (i.e. not tables) are recorded. Arrays are treated as a
+/// single value.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Provenance {
+    /// Origins by key path
+    origins: BTreeMap<String, Origin>,
+}
+
+impl Provenance {
+    /// Create an empty provenance, i.e. all values are defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the origin of the value at the given key path.
+    ///
+    /// Values nested within an array (e.g. `servers.0.host`) are attributed
+    /// to the array. Unrecorded values are defaults.
+    pub fn origin(&self, key: &str) -> &Origin {
+        let mut key = key;
+
+        loop {
+            if let Some(origin) = self.origins.get(key) {
+                return origin;
+            }
+
+            match key.rfind('.') {
+                Some(pos) => key = &key[..pos],
+                None => return &Origin::Default,
+            }
+        }
+    }
+
+    /// Iterate over the recorded key paths and their origins.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Origin)> {
+        self.origins
+            .iter()
+            .map(|(key, origin)| (key.as_str(), origin))
+    }
+
+    /// Record the origin of the value at the given key path.
+    pub fn record(&mut self, key: impl Into<String>, origin: Origin) {
+        self.origins.insert(key.into(), origin);
+    }
+
+    /// Record every leaf value in the given table, computing its origin from
+    /// its key path.
+    pub(crate) fn record_table<F>(&mut self, table: &Table, origin: F)
+    where
+        F: Fn(&[String]) -> Origin,
+    {
+        for (key_path, _) in leaves(table) {
+            let key = key_path.join(".");
+            self.record(key, origin(&key_path));
+        }
+    }
+
+    /// Attribute the values set by the given [profile][`super::profile`] to
+    /// the keys they override, discarding those of all other profiles.
+    pub(crate) fn apply_profile(&mut self, profile: Option<&str>) {
+        let profile_prefix = format!("{}.", super::profile::PROFILE_KEY);
+        let selected_prefix = profile.map(|name| format!("{}{}.", profile_prefix, name));
+        let mut selected = vec![];
+
+        self.origins.retain(|key, origin| {
+            if !key.starts_with(&profile_prefix) {
+                return true;
+            }
+
+            if let Some(prefix) = &selected_prefix {
+                if let Some(key) = key.strip_prefix(prefix.as_str()) {
+                    selected.push((key.to_owned(), origin.clone()));
+                }
+            }
+
+            false
+        });
+
+        self.origins.extend(selected);
+    }
+
+    /// Record the values which differ between the (serialized) configuration
+    /// before and after `Configurable::process_config` as command-line
+    /// options.
+    pub(crate) fn record_overrides(&mut self, before: &Table, after: &Table) {
+        for (key_path, value) in leaves(after) {
+            if lookup(before, &key_path) != Some(value) {
+                self.record(key_path.join("."), Origin::Cli);
+            }
+        }
+    }
+}
+
+/// Serialize a configuration as a TOML table.
+pub(crate) fn to_table<C: Serialize>(config: &C) -> Result<Table, FrameworkError> {
+    match Value::try_from(config) {
+        Ok(Value::Table(table)) => Ok(table),
+        Ok(_) => fail!(ConfigError, "configuration is not a table"),
+        Err(e) => fail!(ConfigError, "error serializing configuration: {}", e),
+    }
+}
+
+/// Get the key paths and values of all leaf (i.e. non-table) values in the
+/// given table.
+pub(crate) fn leaves(table: &Table) -> Vec<(Vec<String>, &Value)> {
+    let mut leaves = vec![];
+
+    for (key, value) in table {
+        match value {
+            Value::Table(t) => {
+                for (mut key_path, value) in self::leaves(t) {
+                    key_path.insert(0, key.clone());
+                    leaves.push((key_path, value));
+                }
+            }
+            _ => leaves.push((vec![key.clone()], value)),
+        }
+    }
+
+    leaves
+}
+
+/// Look up the value at the given key path in a table.
+fn lookup<'a>(table: &'a Table, key_path: &[String]) -> Option<&'a Value> {
+    let (key, parents) = key_path.split_last()?;
+    let mut table = table;
+
+    for segment in parents {
+        table = table.get(segment)?.as_table()?;
+    }
+
+    table.get(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct ExampleConfig {
+        name: String,
+        server: ServerConfig,
+    }
+
+    #[derive(Serialize)]
+    struct ServerConfig {
+        host: String,
+        port: u16,
+    }
+
+    fn example_config(port: u16) -> ExampleConfig {
+        ExampleConfig {
+            name: "example".to_owned(),
+            server: ServerConfig {
+                host: "localhost".to_owned(),
+                port,
+            },
+        }
+    }
+
+    #[test]
+    fn origins() {
+        let mut provenance = Provenance::new();
+        let env = Origin::Env {
+            var: "APP__SERVER__PORT".to_owned(),
+        };
+
+        provenance.record("server.port", env.clone());
+        provenance.record("servers", Origin::Cli);
+
+        assert_eq!(provenance.origin("server.port"), &env);
+        assert_eq!(provenance.origin("servers.0.host"), &Origin::Cli);
+        assert_eq!(provenance.origin("server.host"), &Origin::Default);
+    }
+
+    #[test]
+    fn overrides() {
+        let before = to_table(&example_config(80)).unwrap();
+        let after = to_table(&example_config(8080)).unwrap();
+
+        let mut provenance = Provenance::new();
+        provenance.record_overrides(&before, &after);
+
+        assert_eq!(
+            provenance.iter().collect::<Vec<_>>(),
+            vec![("server.port", &Origin::Cli)]
+        );
+    }
+}
@@ -0,0 +1,64 @@
+//! JSON Schema generation for configuration types.
+//!
+//! Derive [`JsonSchema`] on the application's configuration type (along with
+//! any types it contains) to describe it as a JSON Schema, which editors and
+//! CI can use to validate configuration files. Doc comments on fields become
+//! descriptions in the schema, and `serde` attributes are honored.
+//!
+//! ```ignore
+//! use abscissa_core::config::schema::JsonSchema;
+//!
+//! #[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema)]
+//! #[schemars(crate = "abscissa_core::schemars")]
+//! pub struct MyConfig {
+//!     /// Address to listen on
+//!     pub listen_addr: String,
+//! }
+//! ```
+
+pub use schemars::{schema::RootSchema, JsonSchema};
+
+use crate::FrameworkError;
+
+/// Generate the JSON Schema for the given configuration type.
+pub fn generate<C: JsonSchema>() -> RootSchema {
+    schemars::schema_for!(C)
+}
+
+/// Generate the JSON Schema for the given configuration type, serialized as
+/// pretty-printed JSON.
+pub fn to_json<C: JsonSchema>() -> Result<String, FrameworkError> {
+    let mut json = serde_json::to_string_pretty(&generate::<C>())?;
+    json.push('\n');
+    Ok(json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    /// Example configuration
+    #[allow(dead_code)]
+    #[derive(Deserialize, JsonSchema)]
+    struct ExampleConfig {
+        /// Name of the example
+        name: String,
+
+        /// Port to listen on
+        #[serde(default)]
+        port: u16,
+    }
+
+    #[test]
+    fn descriptions_from_doc_comments() {
+        let schema = serde_json::to_value(generate::<ExampleConfig>()).unwrap();
+
+        assert_eq!(schema["description"], "Example configuration");
+        assert_eq!(
+            schema["properties"]["name"]["description"],
+            "Name of the example"
+        );
+        assert_eq!(schema["required"], serde_json::json!(["name"]));
+    }
+}
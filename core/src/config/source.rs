@@ -0,0 +1,127 @@
+//! Configuration sources: files which are layered to produce a config
+
+use crate::{FrameworkError, FrameworkErrorKind::ConfigError};
+use once_cell::sync::OnceCell;
+use std::{
+    env,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+/// Path which denotes standard input (e.g. `-c -`)
+pub const STDIN_PATH: &str = "-";
+
+/// Name used in place of a path for standard input (e.g. in diagnostics)
+pub const STDIN_NAME: &str = "<stdin>";
+
+/// Contents of standard input, which is only read once so the config can
+/// be reloaded
+static STDIN: OnceCell<String> = OnceCell::new();
+
+/// Configuration source: a file whose contents are merged into the
+/// application's configuration.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Source {
+    /// Path to the configuration file
+    path: PathBuf,
+
+    /// Is it an error if this file is missing?
+    required: bool,
+}
+
+impl Source {
+    /// Create a source for a file which must exist.
+    pub fn required(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            required: true,
+        }
+    }
+
+    /// Create a source which reads the configuration from standard input.
+    ///
+    /// This is equivalent to `Source::required("-")`.
+    pub fn stdin() -> Self {
+        Self::required(STDIN_PATH)
+    }
+
+    /// Create a source for a file which is skipped if it's missing.
+    pub fn optional(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            required: false,
+        }
+    }
+
+    /// Standard set of (optional) sources for the application with the given
+    /// name, in order of increasing precedence:
+    ///
+    /// - `/etc/<app>/<app>.toml` (Unix only)
+    /// - `<user config dir>/<app>/<app>.toml`
+    /// - `./<app>.toml`
+    ///
+    /// Sources given explicitly (e.g. with `--config`) should be added after
+    /// these so they take precedence.
+    pub fn standard(app_name: &str) -> Vec<Self> {
+        let filename = format!("{}.toml", app_name);
+        let mut sources = vec![];
+
+        if cfg!(unix) {
+            let path = Path::new("/etc").join(app_name).join(&filename);
+            sources.push(Self::optional(path));
+        }
+
+        if let Some(dir) = user_config_dir() {
+            sources.push(Self::optional(dir.join(app_name).join(&filename)));
+        }
+
+        sources.push(Self::optional(filename));
+        sources
+    }
+
+    /// Get the path to this source.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Is this source required to exist?
+    pub fn is_required(&self) -> bool {
+        self.required
+    }
+
+    /// Does this source read from standard input (i.e. is its path `-`)?
+    pub fn is_stdin(&self) -> bool {
+        self.path == Path::new(STDIN_PATH)
+    }
+}
+
+/// Read the configuration from standard input.
+///
+/// Standard input is read to completion the first time this is called, and
+/// the same contents are returned on subsequent calls.
+pub(super) fn read_stdin() -> Result<&'static str, FrameworkError> {
+    STDIN
+        .get_or_try_init(|| {
+            let mut contents = String::new();
+            io::stdin().read_to_string(&mut contents)?;
+            Ok(contents)
+        })
+        .map(String::as_str)
+        .map_err(|e: io::Error| {
+            format_err!(ConfigError, "error reading {}: {}", STDIN_NAME, e).into()
+        })
+}
+
+/// Get the current user's configuration directory (if it can be determined).
+fn user_config_dir() -> Option<PathBuf> {
+    if cfg!(windows) {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        env::var_os("HOME").map(|home| Path::new(&home).join("Library/Application Support"))
+    } else {
+        env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+    }
+}
@@ -0,0 +1,405 @@
+//! Handling of unknown keys in configuration sources, e.g. keys written for
+//! a newer version of the application.
+//!
+//! By default, unknown keys are handled by the configuration type itself:
+//! they're ignored unless it's annotated with
+//! `#[serde(deny_unknown_fields)]`. Selecting an [`UnknownKeys`] mode with
+//! [`Loader::unknown_keys`][`super::Loader::unknown_keys`] instead removes
+//! unknown keys before deserializing (so the `deny_unknown_fields` attribute
+//! is overridden) and then either rejects, reports, or ignores them.
+//!
+//! Keys are only recognized as unknown within structs which don't use
+//! `#[serde(flatten)]`.
+
+use serde::de::{self, value::StringDeserializer, DeserializeSeed, Visitor};
+use std::{cell::RefCell, fmt};
+use toml::value::{Table, Value};
+
+/// How to handle unknown keys in configuration sources.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum UnknownKeys {
+    /// Fail with a `ConfigError` on the first unknown key
+    Strict,
+
+    /// Print a warning for each unknown key, and otherwise ignore it
+    Warn,
+
+    /// Ignore unknown keys
+    Silent,
+}
+
+/// Key which isn't a field of the configuration type.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct UnknownKey {
+    /// Key path of the unknown key
+    pub key_path: Vec<String>,
+
+    /// Most similar known key (if any is similar enough)
+    pub suggestion: Option<&'static str>,
+}
+
+impl fmt::Display for UnknownKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown config key `{}`", self.key_path.join("."))?;
+
+        if let Some(suggestion) = self.suggestion {
+            write!(f, " (did you mean `{}`?)", suggestion)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Deserialize the given table, removing (and returning) the keys which
+/// aren't fields of the configuration type.
+pub(crate) fn deserialize<C>(
+    table: Table,
+) -> Result<(C, Vec<UnknownKey>), serde_path_to_error::Error<toml::de::Error>>
+where
+    C: de::DeserializeOwned,
+{
+    let unknown = RefCell::new(vec![]);
+
+    let config = serde_path_to_error::deserialize(Tracker {
+        value: Value::Table(table),
+        key_path: vec![],
+        unknown: &unknown,
+    })?;
+
+    let mut unknown = unknown.into_inner();
+    unknown.sort_by(|a, b| a.key_path.cmp(&b.key_path));
+    Ok((config, unknown))
+}
+
+/// Find the field most similar to the given key, if the edit distance
+/// between them is small enough to suggest it.
+fn suggest(key: &str, fields: &'static [&'static str]) -> Option<&'static str> {
+    let max_distance = std::cmp::max(1, key.chars().count() / 3);
+
+    fields
+        .iter()
+        .map(|field| (edit_distance(key, field), *field))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, field)| field)
+}
+
+/// Compute the edit distance between two strings, counting insertions,
+/// deletions, substitutions, and transpositions of adjacent characters
+/// (i.e. the optimal string alignment distance).
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+
+    for (j, distance) in d[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+
+    d[a.len()][b.len()]
+}
+
+/// Deserializer for a TOML value which records the keys of tables which
+/// aren't fields of the struct they're deserialized into.
+struct Tracker<'a> {
+    /// Value to deserialize
+    value: Value,
+
+    /// Key path of the value
+    key_path: Vec<String>,
+
+    /// Unknown keys found so far
+    unknown: &'a RefCell<Vec<UnknownKey>>,
+}
+
+impl<'de, 'a> de::Deserializer<'de> for Tracker<'a> {
+    type Error = toml::de::Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Value::Table(table) => visitor.visit_map(TableAccess {
+                entries: table.into_iter(),
+                value: None,
+                key_path: self.key_path,
+                unknown: self.unknown,
+            }),
+            Value::Array(values) => visitor.visit_seq(ArrayAccess {
+                values: values.into_iter().enumerate(),
+                key_path: self.key_path,
+                unknown: self.unknown,
+            }),
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_struct<V>(
+        mut self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if let Value::Table(table) = self.value {
+            let mut known = Table::new();
+
+            for (key, value) in table {
+                if fields.contains(&key.as_str()) {
+                    known.insert(key, value);
+                } else {
+                    let mut key_path = self.key_path.clone();
+                    key_path.push(key.clone());
+
+                    self.unknown.borrow_mut().push(UnknownKey {
+                        key_path,
+                        suggestion: suggest(&key, fields),
+                    });
+                }
+            }
+
+            self.value = Value::Table(known);
+        }
+
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.value.deserialize_enum(name, variants, visitor)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map identifier
+        ignored_any
+    }
+}
+
+/// Access to the entries of a table.
+struct TableAccess<'a> {
+    /// Remaining entries
+    entries: <Table as IntoIterator>::IntoIter,
+
+    /// Value of the current entry
+    value: Option<Tracker<'a>>,
+
+    /// Key path of the table
+    key_path: Vec<String>,
+
+    /// Unknown keys found so far
+    unknown: &'a RefCell<Vec<UnknownKey>>,
+}
+
+impl<'de, 'a> de::MapAccess<'de> for TableAccess<'a> {
+    type Error = toml::de::Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        let (key, value) = match self.entries.next() {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+
+        let mut key_path = self.key_path.clone();
+        key_path.push(key.clone());
+
+        self.value = Some(Tracker {
+            value,
+            key_path,
+            unknown: self.unknown,
+        });
+
+        seed.deserialize(StringDeserializer::new(key)).map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let value = self
+            .value
+            .take()
+            .ok_or_else(|| de::Error::custom("value is missing"))?;
+
+        seed.deserialize(value)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+/// Access to the values of an array.
+struct ArrayAccess<'a> {
+    /// Remaining values along with their indexes
+    values: std::iter::Enumerate<std::vec::IntoIter<Value>>,
+
+    /// Key path of the array
+    key_path: Vec<String>,
+
+    /// Unknown keys found so far
+    unknown: &'a RefCell<Vec<UnknownKey>>,
+}
+
+impl<'de, 'a> de::SeqAccess<'de> for ArrayAccess<'a> {
+    type Error = toml::de::Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let (index, value) = match self.values.next() {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+
+        let mut key_path = self.key_path.clone();
+        key_path.push(index.to_string());
+
+        seed.deserialize(Tracker {
+            value,
+            key_path,
+            unknown: self.unknown,
+        })
+        .map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.values.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Default, Deserialize, PartialEq)]
+    #[serde(deny_unknown_fields)]
+    struct ExampleConfig {
+        #[serde(default)]
+        server: ServerConfig,
+
+        #[serde(default)]
+        peers: Vec<PeerConfig>,
+
+        #[serde(default)]
+        tls: Option<TlsConfig>,
+    }
+
+    #[derive(Debug, Default, Deserialize, PartialEq)]
+    #[serde(deny_unknown_fields)]
+    struct ServerConfig {
+        host: String,
+        port: u16,
+    }
+
+    #[derive(Debug, Default, Deserialize, PartialEq)]
+    struct PeerConfig {
+        address: String,
+    }
+
+    #[derive(Debug, Default, Deserialize, PartialEq)]
+    struct TlsConfig {
+        enabled: bool,
+    }
+
+    #[test]
+    fn unknown_keys() {
+        let table: Table = toml::from_str(
+            r#"
+            timeout = 5
+
+            [server]
+            host = "localhost"
+            prot = 80
+            port = 8080
+
+            [[peers]]
+            address = "peer1"
+            adress = "typo"
+
+            [tls]
+            enabled = true
+            certificate = "cert.pem"
+            "#,
+        )
+        .unwrap();
+
+        let (config, unknown) = deserialize::<ExampleConfig>(table).unwrap();
+        assert_eq!(config.server.port, 8080);
+        assert_eq!(config.peers[0].address, "peer1");
+        assert_eq!(config.tls, Some(TlsConfig { enabled: true }));
+
+        let unknown = unknown.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(
+            unknown,
+            [
+                "unknown config key `peers.0.adress` (did you mean `address`?)",
+                "unknown config key `server.prot` (did you mean `port`?)",
+                "unknown config key `timeout`",
+                "unknown config key `tls.certificate`",
+            ]
+        );
+    }
+
+    #[test]
+    fn edit_distances() {
+        assert_eq!(edit_distance("port", "port"), 0);
+        assert_eq!(edit_distance("prot", "port"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("adress", "address"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(suggest("hots", &["host", "port"]), Some("host"));
+        assert_eq!(suggest("timeout", &["host", "port"]), None);
+    }
+}
@@ -4,10 +4,11 @@
 pub mod macros;
 
 pub mod context;
+pub mod exit_code;
 pub mod framework;
 pub mod message;
 
-pub use self::{context::Context, message::Message};
+pub use self::{context::Context, exit_code::ExitCode, message::Message};
 
 /// Box containing a thread-safe + `'static` error suitable for use as a
 /// as an `std::error::Error::source`
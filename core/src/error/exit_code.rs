@@ -0,0 +1,62 @@
+//! Exit codes for errors which terminate an application.
+//!
+//! Abscissa follows the conventions of BSD's `sysexits.h`, e.g. exiting with
+//! `EX_CONFIG` (78) on configuration errors.
+
+use super::{context::Context, framework::FrameworkErrorKind, BoxError};
+use std::fmt::{Debug, Display};
+
+/// Error kinds (or errors) which determine the exit code of an application
+/// terminated by them.
+pub trait ExitCode {
+    /// Exit code of an application terminated by this error (1 by default)
+    fn exit_code(&self) -> i32 {
+        1
+    }
+}
+
+/// `EX_DATAERR`: input data was incorrect
+pub const EX_DATAERR: i32 = 65;
+
+/// `EX_NOINPUT`: an input file didn't exist or wasn't readable
+pub const EX_NOINPUT: i32 = 66;
+
+/// `EX_SOFTWARE`: internal software error
+pub const EX_SOFTWARE: i32 = 70;
+
+/// `EX_OSERR`: operating system error, e.g. a process couldn't be spawned
+pub const EX_OSERR: i32 = 71;
+
+/// `EX_IOERR`: an I/O operation failed
+pub const EX_IOERR: i32 = 74;
+
+/// `EX_TEMPFAIL`: temporary failure, i.e. retrying may succeed
+pub const EX_TEMPFAIL: i32 = 75;
+
+/// `EX_CONFIG`: configuration error
+pub const EX_CONFIG: i32 = 78;
+
+impl ExitCode for FrameworkErrorKind {
+    fn exit_code(&self) -> i32 {
+        match self {
+            #[cfg(feature = "application")]
+            FrameworkErrorKind::ComponentError => EX_SOFTWARE,
+            FrameworkErrorKind::ConfigError => EX_CONFIG,
+            FrameworkErrorKind::IoError => EX_IOERR,
+            FrameworkErrorKind::ParseError => EX_DATAERR,
+            FrameworkErrorKind::PathError { .. } => EX_NOINPUT,
+            FrameworkErrorKind::ProcessError => EX_OSERR,
+            FrameworkErrorKind::ThreadError => EX_SOFTWARE,
+            FrameworkErrorKind::TimeoutError => EX_TEMPFAIL,
+        }
+    }
+}
+
+impl<Kind> ExitCode for Context<Kind>
+where
+    Kind: Clone + Debug + Display + Eq + PartialEq + Into<BoxError> + ExitCode,
+{
+    fn exit_code(&self) -> i32 {
+        self.kind().exit_code()
+    }
+}
@@ -1,6 +1,6 @@
 //! Framework error types
 
-use super::{context::Context, BoxError};
+use super::{context::Context, exit_code::ExitCode, BoxError};
 use std::{
     fmt::{self, Display},
     io,
@@ -32,6 +32,12 @@ impl From<Context<FrameworkErrorKind>> for FrameworkError {
     }
 }
 
+impl ExitCode for FrameworkError {
+    fn exit_code(&self) -> i32 {
+        self.0.exit_code()
+    }
+}
+
 impl std::error::Error for FrameworkError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         self.0.source()
@@ -118,3 +124,24 @@ impl From<toml::de::Error> for FrameworkError {
         FrameworkErrorKind::ParseError.context(err).into()
     }
 }
+
+#[cfg(feature = "serde_json")]
+impl From<serde_json::Error> for FrameworkError {
+    fn from(err: serde_json::Error) -> Self {
+        FrameworkErrorKind::ParseError.context(err).into()
+    }
+}
+
+#[cfg(feature = "serde_yaml")]
+impl From<serde_yaml::Error> for FrameworkError {
+    fn from(err: serde_yaml::Error) -> Self {
+        FrameworkErrorKind::ParseError.context(err).into()
+    }
+}
+
+#[cfg(feature = "serde_ron")]
+impl From<serde_ron::error::SpannedError> for FrameworkError {
+    fn from(err: serde_ron::error::SpannedError) -> Self {
+        FrameworkErrorKind::ParseError.context(err).into()
+    }
+}
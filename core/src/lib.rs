@@ -40,6 +40,8 @@ pub mod prelude;
 mod runnable;
 #[cfg(feature = "application")]
 mod shutdown;
+#[cfg(all(unix, feature = "signals"))]
+pub mod signal;
 #[cfg(feature = "testing")]
 pub mod testing;
 pub mod thread;
@@ -50,7 +52,7 @@ pub mod trace;
 
 pub use crate::{
     error::framework::{FrameworkError, FrameworkErrorKind},
-    runnable::Runnable,
+    runnable::{Runnable, TryRunnable},
 };
 pub use std::collections::{btree_map as map, btree_set as set, BTreeMap as Map};
 
@@ -72,6 +74,8 @@ pub use crate::{command::Command, path::StandardPaths};
 #[cfg(feature = "options")]
 pub use clap;
 pub use fs_err as fs;
+#[cfg(feature = "schema")]
+pub use schemars;
 #[cfg(feature = "secrets")]
 pub use secrecy as secret;
 #[cfg(feature = "secrets")]
@@ -13,6 +13,10 @@ use crate::FrameworkError;
 /// Name of the application's secrets directory
 pub(crate) const SECRETS_DIR: &str = "secrets";
 
+/// Name of the directory crash reports are written to
+#[cfg(feature = "application")]
+pub(crate) const CRASH_REPORTS_DIR: &str = "crash-reports";
+
 /// Path to the application's executable.
 pub trait ExePath {
     /// Get the path to the application's executable
@@ -55,6 +59,9 @@ impl StandardPaths {
     /// - `./` (root): application root directory
     /// - `./{{~name~}}` (bin): application executable path
     /// - `./secrets` (secrets): location of files containing app's secrets
+    ///
+    /// Crash reports are written to `./crash-reports` by default (see
+    /// `Application::crash_reports_dir`).
     fn from_exe_path<P>(exe_path: P) -> Result<Self, FrameworkError>
     where
         P: Into<AbsPathBuf>,
@@ -1,7 +1,7 @@
 //! Core prelude: imported in every application's `prelude.rs`
 
 /// Commonly used Abscissa traits
-pub use crate::{Application, Command, Runnable};
+pub use crate::{Application, Command, Runnable, TryRunnable};
 
 /// Error macros
 pub use crate::{ensure, fail, fatal, format_err};
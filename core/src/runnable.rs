@@ -1,7 +1,10 @@
-//! `Runnable` trait.
+//! `Runnable` and `TryRunnable` traits.
 
 #[doc(hidden)]
-pub use abscissa_derive::Runnable;
+pub use abscissa_derive::{Runnable, TryRunnable};
+
+use crate::{error::ExitCode, FrameworkError};
+use std::error::Error;
 
 /// `Runnable` is a common trait for things which can be run without any
 /// arguments.
@@ -13,11 +16,72 @@ pub trait Runnable {
 }
 
 /// `RunnableMut` is a `Runnable` that takes a mutable reference to `self`.
+#[allow(dead_code)]
 pub trait RunnableMut {
     /// Run this `RunnableMut`
     fn run(&mut self);
 }
 
+/// `TryRunnable` is a fallible `Runnable`.
+///
+/// When an application's command returns an error, `Application::run` shuts
+/// the application down and exits with the error's [`ExitCode`].
+///
+/// It's implemented for all `Runnable` types, and can be derived for enums
+/// whose variants are `TryRunnable`: their errors are converted with `Into`
+/// to the error type given by `#[runnable(error = "...")]` (by default
+/// `FrameworkError`).
+///
+/// ```
+/// use abscissa_core::{FrameworkError, Runnable, TryRunnable};
+///
+/// struct StartCmd {}
+///
+/// impl Runnable for StartCmd {
+///     fn run(&self) {}
+/// }
+///
+/// #[derive(TryRunnable)]
+/// #[runnable(error = "FrameworkError")]
+/// enum MyCmd {
+///     Start(StartCmd),
+/// }
+/// ```
+///
+/// Malformed `runnable` attributes are reported as compile errors:
+///
+/// ```compile_fail
+/// # use abscissa_core::{Runnable, TryRunnable};
+/// # struct StartCmd {}
+/// # impl Runnable for StartCmd {
+/// #     fn run(&self) {}
+/// # }
+/// #[derive(TryRunnable)]
+/// #[runnable(error = "not a path")]
+/// enum MyCmd {
+///     Start(StartCmd),
+/// }
+/// ```
+pub trait TryRunnable {
+    /// Error returned when running fails
+    type Error: Error + ExitCode;
+
+    /// Run this `TryRunnable`
+    fn try_run(&self) -> Result<(), Self::Error>;
+}
+
+impl<R> TryRunnable for R
+where
+    R: Runnable,
+{
+    type Error = FrameworkError;
+
+    fn try_run(&self) -> Result<(), FrameworkError> {
+        self.run();
+        Ok(())
+    }
+}
+
 impl Runnable for Box<dyn Fn()> {
     fn run(&self) {
         self();
@@ -32,7 +96,10 @@ impl RunnableMut for Box<dyn FnMut()> {
 
 #[cfg(test)]
 mod tests {
-    use crate::Runnable;
+    use crate::{
+        error::ExitCode, runnable::TryRunnable, FrameworkError, FrameworkErrorKind::ConfigError,
+        Runnable,
+    };
     use std::sync::Mutex;
 
     #[allow(dead_code)]
@@ -84,4 +151,39 @@ mod tests {
         };
         assert!(variant_b.was_called());
     }
+
+    #[derive(TryRunnable)]
+    #[runnable(error = "FrameworkError")]
+    enum TestTryEnum {
+        Infallible(VariantB),
+        Fallible(FallibleVariant),
+    }
+
+    struct FallibleVariant {}
+
+    impl TryRunnable for FallibleVariant {
+        type Error = FrameworkError;
+
+        fn try_run(&self) -> Result<(), FrameworkError> {
+            fail!(ConfigError, "invalid config")
+        }
+    }
+
+    #[test]
+    fn custom_derive_try_runnable() {
+        let ex = TestTryEnum::Infallible(VariantB::default());
+        ex.try_run().unwrap();
+
+        match ex {
+            TestTryEnum::Infallible(b) => assert!(b.was_called()),
+            TestTryEnum::Fallible(_) => panic!("this shouldn't be!"),
+        }
+
+        let err = TestTryEnum::Fallible(FallibleVariant {})
+            .try_run()
+            .unwrap_err();
+
+        assert_eq!(*err.kind(), ConfigError);
+        assert_eq!(err.exit_code(), 78);
+    }
 }
@@ -1,5 +1,11 @@
 //! Application shutdown support
 
+use std::time::Duration;
+
+/// Interval at which shutdowns in progress check whether they've been
+/// escalated, e.g. by another signal.
+pub(crate) const ESCALATION_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
 /// Types of shutdown recognized by Abscissa
 #[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
 pub enum Shutdown {
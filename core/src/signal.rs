@@ -0,0 +1,259 @@
+//! Unix signal handling.
+//!
+//! Applications handle the signals listed by [`Application::signals`]
+//! (`SIGINT` and `SIGTERM` by default) on a dedicated thread, which passes
+//! each one to [`Application::handle_signal`]. By default these signals
+//! initiate a graceful shutdown: the kill switches of all threads spawned by
+//! the application's [`thread::Manager`][`crate::thread::Manager`] are
+//! thrown and `Component::before_shutdown` is invoked on every component.
+//! If another signal arrives while the shutdown is in progress (e.g. Ctrl-C
+//! is pressed twice), it's escalated to a forced shutdown: the component
+//! being shut down gracefully (if any) is abandoned, and the remaining ones
+//! are shut down with `Shutdown::Forced`.
+//!
+//! After a shutdown initiated by a signal, the application exits with status
+//! `128 + N` where `N` is the signal's number (e.g. 130 for `SIGINT`), as
+//! shells do for processes killed by signals.
+//!
+//! Signals which don't shut the application down (e.g. `SIGHUP` or
+//! `SIGUSR1`) are passed to the `Component::on_signal` hooks of the
+//! components which list them in `Component::signals`. These hooks run on a
+//! thread registered with the thread manager rather than in the signal
+//! handler, and errors they return are logged rather than terminating the
+//! application. On `SIGHUP`, that thread first reloads the application's
+//! configuration with `Application::reload_config`.
+//!
+//! Applications can opt out by returning an empty list from
+//! `Application::signals` (or by disabling the `signals` cargo feature), or
+//! handle signals differently by overriding `Application::handle_signal`.
+
+use crate::{
+    application::{AppCell, Application},
+    shutdown::Shutdown,
+    FrameworkError,
+};
+use signal_hook::{consts, iterator::Signals};
+use std::{
+    fmt::{self, Display},
+    sync::mpsc::{self, RecvTimeoutError},
+    thread,
+    time::Duration,
+};
+
+/// Name of the thread which runs the components' signal hooks
+const HOOKS_THREAD: &str = "abscissa-signal-hooks";
+
+/// Interval at which the hooks thread checks whether it should terminate
+const HOOKS_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Unix signals handled by Abscissa applications.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum Signal {
+    /// `SIGINT`: interrupt from the keyboard (i.e. Ctrl-C)
+    Interrupt,
+
+    /// `SIGTERM`: request to terminate
+    Terminate,
+
+    /// `SIGHUP`: conventionally a request for daemons to reload their
+    /// configuration
+    Hangup,
+
+    /// `SIGUSR1`: user-defined signal 1, e.g. to dump state or reopen log
+    /// files
+    User1,
+
+    /// `SIGUSR2`: user-defined signal 2
+    User2,
+}
+
+impl Signal {
+    /// Signals which request that the application shut down.
+    pub const SHUTDOWN: &'static [Signal] = &[Signal::Interrupt, Signal::Terminate];
+
+    /// Get the number of this signal.
+    pub fn number(self) -> i32 {
+        match self {
+            Signal::Interrupt => consts::SIGINT,
+            Signal::Terminate => consts::SIGTERM,
+            Signal::Hangup => consts::SIGHUP,
+            Signal::User1 => consts::SIGUSR1,
+            Signal::User2 => consts::SIGUSR2,
+        }
+    }
+
+    /// Find the signal with the given number (if it's handled by Abscissa).
+    pub fn from_number(number: i32) -> Option<Self> {
+        match number {
+            consts::SIGINT => Some(Signal::Interrupt),
+            consts::SIGTERM => Some(Signal::Terminate),
+            consts::SIGHUP => Some(Signal::Hangup),
+            consts::SIGUSR1 => Some(Signal::User1),
+            consts::SIGUSR2 => Some(Signal::User2),
+            _ => None,
+        }
+    }
+
+    /// Get the name of this signal, e.g. `SIGINT`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Signal::Interrupt => "SIGINT",
+            Signal::Terminate => "SIGTERM",
+            Signal::Hangup => "SIGHUP",
+            Signal::User1 => "SIGUSR1",
+            Signal::User2 => "SIGUSR2",
+        }
+    }
+
+    /// Exit status of a process which terminated due to this signal.
+    pub fn exit_code(self) -> i32 {
+        128 + self.number()
+    }
+}
+
+impl Display for Signal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Register handlers for the application's signals, spawning a thread which
+/// dispatches them to `Application::handle_signal` (along with a managed
+/// thread which runs the components' signal hooks, if needed).
+pub(crate) fn install<A: Application + Send + Sync>(
+    app_cell: &'static AppCell<A>,
+) -> Result<(), FrameworkError> {
+    let signals = app_cell.signals();
+
+    if signals.is_empty() {
+        return Ok(());
+    }
+
+    let hooks = if signals
+        .iter()
+        .any(|signal| !Signal::SHUTDOWN.contains(signal))
+    {
+        Some(spawn_hooks_thread(app_cell)?)
+    } else {
+        None
+    };
+
+    let mut received = Signals::new(signals.iter().map(|signal| signal.number()))?;
+
+    thread::Builder::new()
+        .name("abscissa-signals".to_owned())
+        .spawn(move || {
+            // Whether a shutdown has been initiated by a signal
+            let mut shutting_down = false;
+
+            for number in received.forever() {
+                if let Some(signal) = Signal::from_number(number) {
+                    dispatch(app_cell, signal, hooks.as_ref(), &mut shutting_down);
+                }
+            }
+        })?;
+
+    Ok(())
+}
+
+/// Spawn the thread which runs the components' signal hooks, returning the
+/// channel for sending it signals.
+fn spawn_hooks_thread<A: Application + Send + Sync>(
+    app_cell: &'static AppCell<A>,
+) -> Result<mpsc::Sender<Signal>, FrameworkError> {
+    let (sender, receiver) = mpsc::channel();
+
+    app_cell
+        .state()
+        .threads_mut()
+        .spawn(crate::thread::Name::new(HOOKS_THREAD)?, move || {
+            while !crate::thread::should_terminate() {
+                match receiver.recv_timeout(HOOKS_POLL_INTERVAL) {
+                    Ok(signal) => run_hooks(&**app_cell, signal),
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+            }
+        })?;
+
+    Ok(sender)
+}
+
+/// Run the hooks for a signal which doesn't shut the application down,
+/// reloading its configuration first on `SIGHUP`.
+fn run_hooks<A: Application>(app: &A, signal: Signal) {
+    if signal == Signal::Hangup {
+        if let Err(e) = app.reload_config() {
+            error!("error reloading config on {}: {}", signal, e);
+        }
+    }
+
+    app.state().components().on_signal(signal);
+}
+
+/// Handle a signal received by the application, shutting it down if
+/// requested and otherwise passing it to the components' signal hooks.
+fn dispatch<A: Application + Send + Sync>(
+    app_cell: &'static AppCell<A>,
+    signal: Signal,
+    hooks: Option<&mpsc::Sender<Signal>>,
+    shutting_down: &mut bool,
+) {
+    let shutdown = match app_cell.handle_signal(signal) {
+        Some(shutdown) => shutdown,
+        None => {
+            if let Some(hooks) = hooks {
+                hooks.send(signal).ok();
+            }
+
+            return;
+        }
+    };
+
+    // Escalate the shutdown in progress (if any), which then skips (or
+    // stops waiting for) the remaining graceful shutdown steps rather than
+    // running another shutdown alongside it
+    if *shutting_down || app_cell.state().is_shutting_down() {
+        app_cell.state().escalate_shutdown();
+        return;
+    }
+
+    *shutting_down = true;
+
+    // Graceful shutdowns run on their own thread so subsequent signals can
+    // still be received (and escalate them)
+    if shutdown == Shutdown::Graceful {
+        let spawned = thread::Builder::new()
+            .name("abscissa-shutdown".to_owned())
+            .spawn(move || app_cell.shutdown_with_exit_code(shutdown, signal.exit_code()));
+
+        if spawned.is_ok() {
+            return;
+        }
+    }
+
+    app_cell.shutdown_with_exit_code(shutdown, signal.exit_code())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers() {
+        for &signal in &[
+            Signal::Interrupt,
+            Signal::Terminate,
+            Signal::Hangup,
+            Signal::User1,
+            Signal::User2,
+        ] {
+            assert_eq!(Signal::from_number(signal.number()), Some(signal));
+        }
+
+        assert_eq!(Signal::Interrupt.exit_code(), 130);
+        assert_eq!(Signal::Terminate.to_string(), "SIGTERM");
+        assert_eq!(Signal::from_number(consts::SIGKILL), None);
+    }
+}
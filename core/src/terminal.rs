@@ -28,6 +28,11 @@ pub fn streams() -> &'static Streams {
         .expect("terminal streams not yet initialized!")
 }
 
+/// Have the terminal [`Streams`] been initialized?
+pub(crate) fn is_initialized() -> bool {
+    STREAMS.get().is_some()
+}
+
 /// Get the standard output stream
 pub fn stdout() -> &'static StandardStream {
     &streams().stdout
@@ -8,7 +8,7 @@
 //!
 //! The main entrypoint for running tests is [`CmdRunner`].
 
-mod config;
+pub mod config;
 pub mod prelude;
 pub mod process;
 mod regex;
@@ -1,6 +1,9 @@
 //! Support for writing config files and using them in tests
 
-use crate::fs::{self, File, OpenOptions};
+use crate::{
+    config::Format,
+    fs::{self, File, OpenOptions},
+};
 use serde::Serialize;
 use std::{
     env,
@@ -20,18 +23,28 @@ pub struct ConfigFile {
 }
 
 impl ConfigFile {
-    /// Create a config file by serializing it to the given location
+    /// Create a config file by serializing it to the given location as TOML
     pub fn create<C>(app_name: &OsStr, config: &C) -> Self
     where
         C: Serialize,
     {
-        let (path, mut file) = Self::open(app_name);
+        Self::create_with_format(app_name, config, Format::Toml)
+    }
+
+    /// Create a config file by serializing it to the given location in the
+    /// given format (which determines the file's extension)
+    pub fn create_with_format<C>(app_name: &OsStr, config: &C, format: Format) -> Self
+    where
+        C: Serialize,
+    {
+        let (path, mut file) = Self::open(app_name, format.extension());
 
-        let config_toml = toml::to_string_pretty(config)
-            .unwrap_or_else(|e| panic!("error serializing config as TOML: {}", e))
+        let contents = format
+            .serialize(config)
+            .unwrap_or_else(|e| panic!("{}", e))
             .into_bytes();
 
-        file.write_all(&config_toml)
+        file.write_all(&contents)
             .unwrap_or_else(|e| panic!("error writing config to {}: {}", path.display(), e));
 
         Self { path }
@@ -43,12 +56,12 @@ impl ConfigFile {
     }
 
     /// Create a temporary filename for the config
-    fn open(app_name: &OsStr) -> (PathBuf, File) {
+    fn open(app_name: &OsStr, extension: &str) -> (PathBuf, File) {
         // TODO: fully `OsString`-based path building
         let filename_prefix = app_name.to_string_lossy().to_string();
 
         for n in 0..FILE_CREATE_ATTEMPTS {
-            let filename = format!("{}-{}.toml", &filename_prefix, n);
+            let filename = format!("{}-{}.{}", &filename_prefix, n, extension);
             let path = env::temp_dir().join(filename);
 
             match OpenOptions::new().write(true).create_new(true).open(&path) {
@@ -64,8 +77,8 @@ impl ConfigFile {
         }
 
         panic!(
-            "couldn't create {}.toml after {} attempts!",
-            filename_prefix, FILE_CREATE_ATTEMPTS
+            "couldn't create {}.{} after {} attempts!",
+            filename_prefix, extension, FILE_CREATE_ATTEMPTS
         )
     }
 }
@@ -8,6 +8,8 @@ pub use self::{
     streams::{OutputStream, Stderr, Stdout},
 };
 
+#[cfg(all(unix, feature = "signals"))]
+use crate::signal::Signal;
 use crate::{
     FrameworkError,
     FrameworkErrorKind::{ProcessError, TimeoutError},
@@ -38,8 +40,8 @@ pub struct Process<'cmd> {
     /// Standard error (if captured)
     stderr: Option<Stderr>,
 
-    /// Standard input
-    stdin: ChildStdin,
+    /// Standard input (unless it was already closed)
+    stdin: Option<ChildStdin>,
 
     /// Optional mutex guard ensuring exclusive access to this process
     guard: Option<Guard<'cmd>>,
@@ -52,7 +54,7 @@ impl<'cmd> Process<'cmd> {
     pub(super) fn new(mut child: Child, timeout: Duration, guard: Option<Guard<'cmd>>) -> Self {
         let stdout = child.stdout.take().map(Stdout::new);
         let stderr = child.stderr.take().map(Stderr::new);
-        let stdin = child.stdin.take().unwrap();
+        let stdin = child.stdin.take();
 
         Self {
             child,
@@ -82,8 +84,34 @@ impl<'cmd> Process<'cmd> {
             .expect("child stderr not captured (use 'capture_stderr' method)")
     }
 
-    /// Wait for the child to exit
+    /// Close the child's stdin, signaling the end of its input.
+    pub fn close_stdin(&mut self) {
+        self.stdin = None;
+    }
+
+    /// Send the given signal to the child (using the `kill` utility).
+    #[cfg(all(unix, feature = "signals"))]
+    pub fn signal(&self, signal: Signal) -> Result<(), FrameworkError> {
+        let status = std::process::Command::new("kill")
+            .arg("-s")
+            .arg(signal.name().trim_start_matches("SIG"))
+            .arg(self.child.id().to_string())
+            .status()?;
+
+        ensure!(
+            status.success(),
+            ProcessError,
+            "couldn't send {} to subprocess",
+            signal
+        );
+
+        Ok(())
+    }
+
+    /// Wait for the child to exit (closing its stdin first)
     pub fn wait(mut self) -> Result<ExitStatus<'cmd>, FrameworkError> {
+        self.close_stdin();
+
         match self.child.wait_timeout(self.timeout)? {
             Some(status) => {
                 let code = status.code().ok_or_else(|| {
@@ -101,12 +129,21 @@ impl<'cmd> Process<'cmd> {
     }
 }
 
+impl<'cmd> Process<'cmd> {
+    /// Get the child's stdin, returning an error if it was closed.
+    fn stdin_mut(&mut self) -> io::Result<&mut ChildStdin> {
+        self.stdin
+            .as_mut()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::BrokenPipe, "child stdin closed"))
+    }
+}
+
 impl<'cmd> Write for Process<'cmd> {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        self.stdin.write(buf)
+        self.stdin_mut()?.write(buf)
     }
 
     fn flush(&mut self) -> io::Result<()> {
-        self.stdin.flush()
+        self.stdin_mut()?.flush()
     }
 }
@@ -4,6 +4,7 @@ use super::{
     config::ConfigFile,
     process::{ExitStatus, Process},
 };
+use crate::config::Format;
 use serde::Serialize;
 use std::{
     ffi::OsString,
@@ -32,6 +33,9 @@ pub struct CmdRunner {
     /// Arguments to pass to the executable
     args: Vec<OsString>,
 
+    /// Environment variables to set for the executable
+    envs: Vec<(OsString, OsString)>,
+
     /// Capture standard output to a pipe
     capture_stdout: bool,
 
@@ -41,6 +45,9 @@ pub struct CmdRunner {
     /// Optional configuration file (deleted when no-longer used)
     config: Option<Arc<ConfigFile>>,
 
+    /// Optional serialized configuration to write to the child's stdin
+    config_stdin: Option<Arc<String>>,
+
     /// Print invocation info
     print_info: bool,
 
@@ -100,9 +107,11 @@ impl CmdRunner {
             program: program.into(),
             target_bin: None,
             args: vec![],
+            envs: vec![],
             capture_stdout: false,
             capture_stderr: false,
             config: None,
+            config_stdin: None,
             print_info: true,
             mutex: None,
             timeout: None,
@@ -128,6 +137,16 @@ impl CmdRunner {
         self
     }
 
+    /// Set an environment variable for the executable
+    pub fn env<K, V>(&mut self, key: K, value: V) -> &mut Self
+    where
+        K: Into<OsString>,
+        V: Into<OsString>,
+    {
+        self.envs.push((key.into(), value.into()));
+        self
+    }
+
     /// Enable capturing of standard output
     pub fn capture_stdout(&mut self) -> &mut Self {
         self.capture_stdout = true;
@@ -145,7 +164,15 @@ impl CmdRunner {
     where
         C: Serialize,
     {
-        if self.config.is_some() {
+        self.config_with_format(config, Format::Toml)
+    }
+
+    /// Add the given configuration file, serialized in the given format
+    pub fn config_with_format<C>(&mut self, config: &C, format: Format) -> &mut Self
+    where
+        C: Serialize,
+    {
+        if self.config.is_some() || self.config_stdin.is_some() {
             panic!("config file already added");
         }
 
@@ -155,7 +182,7 @@ impl CmdRunner {
             .cloned()
             .unwrap_or_else(|| "app".into());
 
-        let config_file = ConfigFile::create(&target_bin, config);
+        let config_file = ConfigFile::create_with_format(&target_bin, config, format);
 
         // Add `abscissa_core::EntryPoint`-compatible args to override config
         self.arg("-c");
@@ -165,6 +192,28 @@ impl CmdRunner {
         self
     }
 
+    /// Add the given configuration, serialized as TOML and written to the
+    /// command's standard input (passing `-c -`)
+    pub fn config_stdin<C>(&mut self, config: &C) -> &mut Self
+    where
+        C: Serialize,
+    {
+        if self.config.is_some() || self.config_stdin.is_some() {
+            panic!("config file already added");
+        }
+
+        let contents = Format::Toml
+            .serialize(config)
+            .unwrap_or_else(|e| panic!("{}", e));
+
+        // Add `abscissa_core::EntryPoint`-compatible args to read config from stdin
+        self.arg("-c");
+        self.arg("-");
+
+        self.config_stdin = Some(Arc::new(contents));
+        self
+    }
+
     /// Serialize invocations of this command using a mutex
     pub fn exclusive(&mut self) -> &mut Self {
         if self.mutex.is_none() {
@@ -212,6 +261,7 @@ impl CmdRunner {
 
         let child = Command::new(&self.program)
             .args(&self.args)
+            .envs(self.envs.iter().map(|(key, value)| (key, value)))
             .stdin(Stdio::piped())
             .stdout(stdout)
             .stderr(stderr)
@@ -220,7 +270,16 @@ impl CmdRunner {
                 panic!("error running command: {}", e);
             });
 
-        Process::new(child, self.timeout.unwrap_or(DEFAULT_TIMEOUT), guard)
+        let mut process = Process::new(child, self.timeout.unwrap_or(DEFAULT_TIMEOUT), guard);
+
+        if let Some(contents) = &self.config_stdin {
+            process
+                .write_all(contents.as_bytes())
+                .unwrap_or_else(|e| panic!("error writing config to stdin: {}", e));
+            process.close_stdin();
+        }
+
+        process
     }
 
     /// Get the exit status for the given subcommand
@@ -10,7 +10,14 @@ pub use self::{manager::Manager, name::Name};
 
 use self::kill_switch::KillSwitch;
 use crate::{FrameworkError, FrameworkErrorKind::ThreadError};
-use std::{io, sync::Arc, thread};
+use std::{
+    io,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+};
 
 /// Join handles for Abscissa-managed threads.
 pub type JoinHandle = thread::JoinHandle<()>;
@@ -24,6 +31,9 @@ pub struct Thread {
     /// Kill switch used to terminate the thread
     kill_switch: Arc<KillSwitch>,
 
+    /// Flag set when the thread exits (including by panicking)
+    finished: Arc<AtomicBool>,
+
     /// Join handle to the thread
     handle: JoinHandle,
 }
@@ -35,11 +45,18 @@ impl Thread {
         F: FnOnce() + Send + 'static,
     {
         let kill_switch = Arc::new(KillSwitch::new());
-        let handle = spawn_thread(name.clone(), Arc::clone(&kill_switch), f)?;
+        let finished = Arc::new(AtomicBool::new(false));
+        let handle = spawn_thread(
+            name.clone(),
+            Arc::clone(&kill_switch),
+            Arc::clone(&finished),
+            f,
+        )?;
 
         Ok(Self {
             name,
             kill_switch,
+            finished,
             handle,
         })
     }
@@ -59,6 +76,11 @@ impl Thread {
         self.kill_switch.throw();
     }
 
+    /// Has this thread exited (i.e. can it be joined without blocking)?
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::Acquire)
+    }
+
     /// Join to a running thread, waiting for it to finish
     pub fn join(self) -> Result<(), FrameworkError> {
         // Trigger the kill switch in order to signal the thread to stop.
@@ -82,14 +104,30 @@ pub fn should_terminate() -> bool {
 }
 
 /// Spawn a thread
-fn spawn_thread<F>(name: Name, kill_switch: Arc<KillSwitch>, f: F) -> Result<JoinHandle, io::Error>
+fn spawn_thread<F>(
+    name: Name,
+    kill_switch: Arc<KillSwitch>,
+    finished: Arc<AtomicBool>,
+    f: F,
+) -> Result<JoinHandle, io::Error>
 where
     F: FnOnce() + Send + 'static,
 {
     // NOTE: `Name` ensures the absence of null bytes, which should prevent the
     // only condition under which this function could potentially panic.
     thread::Builder::new().name(name.into()).spawn(move || {
+        let _finished = FinishedGuard(finished);
         kill_switch::set(kill_switch);
         f()
     })
 }
+
+/// Guard which flags a thread as finished when dropped, i.e. when the thread
+/// returns or unwinds.
+struct FinishedGuard(Arc<AtomicBool>);
+
+impl Drop for FinishedGuard {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Release);
+    }
+}
@@ -2,7 +2,14 @@
 
 use super::{Name, Thread};
 use crate::{FrameworkError, FrameworkErrorKind::ThreadError, Map};
-use std::{convert::TryInto, sync};
+use std::{
+    convert::TryInto,
+    mem, sync, thread,
+    time::{Duration, Instant},
+};
+
+/// Interval at which `Manager::join_timeout` checks whether threads exited
+const JOIN_POLL_INTERVAL: Duration = Duration::from_millis(10);
 
 /// Reader guard for the thread manager.
 pub type Reader<'a> = sync::RwLockReadGuard<'a, Manager>;
@@ -39,18 +46,23 @@ impl Manager {
         Ok(())
     }
 
+    /// Signal all running threads to terminate (i.e. throw their kill
+    /// switches) without waiting for them to exit.
+    pub fn request_termination(&self) {
+        for thread in self.threads.values() {
+            thread.request_termination();
+        }
+    }
+
     /// Signal all running threads to terminate and then join them
     pub fn join(&mut self) -> Result<(), FrameworkError> {
-        let mut names = Vec::with_capacity(self.threads.len());
-
         // Send termination request in advance prior to joining
-        for (name, thread) in self.threads.iter() {
-            names.push(name.clone());
-            thread.request_termination();
-        }
+        self.request_termination();
 
         // TODO(tarcieri): use `BTreeMap::into_values` when stable
         // See: <https://github.com/rust-lang/rust/issues/75294>
+        let names = self.threads.keys().cloned().collect::<Vec<_>>();
+
         for name in names.into_iter() {
             if let Some(thread) = self.threads.remove(&name) {
                 thread.join()?;
@@ -59,4 +71,110 @@ impl Manager {
 
         Ok(())
     }
+
+    /// Signal all running threads to terminate and then join them, waiting
+    /// at most the given timeout for them to exit.
+    ///
+    /// Returns an error naming the threads which panicked or didn't exit in
+    /// time (the latter are left running, detached from the manager). If
+    /// called from a managed thread, that thread is neither waited for nor
+    /// joined.
+    pub fn join_timeout(&mut self, timeout: Duration) -> Result<(), FrameworkError> {
+        self.request_termination();
+
+        let current = thread::current().id();
+        let deadline = Instant::now() + timeout;
+
+        while Instant::now() < deadline
+            && !self
+                .threads
+                .values()
+                .all(|t| t.is_finished() || t.handle.thread().id() == current)
+        {
+            thread::sleep(JOIN_POLL_INTERVAL);
+        }
+
+        let mut panicked = vec![];
+        let mut running = vec![];
+
+        for (name, thread) in mem::take(&mut self.threads) {
+            if thread.handle.thread().id() == current {
+                continue;
+            } else if !thread.is_finished() {
+                running.push(name.to_string());
+            } else if thread.join().is_err() {
+                panicked.push(name.to_string());
+            }
+        }
+
+        let mut failures = vec![];
+
+        if !panicked.is_empty() {
+            failures.push(format!("panicked: {}", panicked.join(", ")));
+        }
+
+        if !running.is_empty() {
+            failures.push(format!(
+                "didn't exit within {:?}: {}",
+                timeout,
+                running.join(", ")
+            ));
+        }
+
+        ensure!(
+            failures.is_empty(),
+            ThreadError,
+            "threads {}",
+            failures.join("; ")
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::thread::should_terminate;
+
+    #[test]
+    fn join_timeout() {
+        let mut manager = Manager::default();
+
+        manager
+            .spawn(Name::new("cooperative").unwrap(), || {
+                while !should_terminate() {
+                    thread::sleep(Duration::from_millis(1));
+                }
+            })
+            .unwrap();
+
+        manager.join_timeout(Duration::from_secs(5)).unwrap();
+        assert!(manager.threads.is_empty());
+    }
+
+    #[test]
+    fn join_timeout_failures() {
+        let mut manager = Manager::default();
+
+        manager
+            .spawn(Name::new("stubborn").unwrap(), || {
+                thread::sleep(Duration::from_secs(60))
+            })
+            .unwrap();
+
+        manager
+            .spawn(Name::new("panicking").unwrap(), || panic!("thread panic"))
+            .unwrap();
+
+        let err = manager
+            .join_timeout(Duration::from_millis(100))
+            .unwrap_err();
+
+        assert_eq!(*err.kind(), ThreadError);
+        assert_eq!(
+            err.to_string(),
+            "thread error: threads panicked: panicking; didn't exit within 100ms: stubborn"
+        );
+    }
 }
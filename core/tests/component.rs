@@ -2,8 +2,15 @@
 
 mod example_app;
 
-use self::example_app::{ExampleApp, ExampleConfig};
-use abscissa_core::{component, Component, FrameworkError, FrameworkErrorKind::ComponentError};
+use self::example_app::{ExampleApp, ExampleCommand, ExampleConfig};
+use abscissa_core::{
+    application, component,
+    config::{CfgCell, Loader},
+    fail, format_err, Application, Component, FrameworkError,
+    FrameworkErrorKind::{ComponentError, ConfigError},
+    Shutdown, Version,
+};
+use std::{slice, sync::Mutex};
 
 /// ID for `FoobarComponent` (example component #1)
 const FOOBAR_COMPONENT_ID: component::Id = component::Id::new("component::FoobarComponent");
@@ -59,6 +66,207 @@ impl QuuxComponent {
     }
 }
 
+/// Example component #4: tracks config reloads
+#[derive(Debug, Default)]
+pub struct ReloadComponent {
+    /// Value from the most recently accepted config
+    pub value: u32,
+}
+
+impl Component<ExampleApp> for ReloadComponent {
+    fn id(&self) -> component::Id {
+        component::Id::new("component::ReloadComponent")
+    }
+
+    fn version(&self) -> Version {
+        Version::parse(env!("CARGO_PKG_VERSION")).unwrap()
+    }
+
+    fn on_config_reload(
+        &mut self,
+        _old: &ExampleConfig,
+        new: &ExampleConfig,
+    ) -> Result<(), FrameworkError> {
+        self.value = new.value;
+        Ok(())
+    }
+}
+
+/// Example component #5: rejects all config reloads
+#[derive(Debug, Default)]
+pub struct RejectReloadComponent {}
+
+impl Component<ExampleApp> for RejectReloadComponent {
+    fn id(&self) -> component::Id {
+        component::Id::new("component::RejectReloadComponent")
+    }
+
+    fn version(&self) -> Version {
+        Version::parse(env!("CARGO_PKG_VERSION")).unwrap()
+    }
+
+    fn on_config_reload(
+        &mut self,
+        _old: &ExampleConfig,
+        _new: &ExampleConfig,
+    ) -> Result<(), FrameworkError> {
+        fail!(ConfigError, "reload rejected");
+    }
+}
+
+/// Example component #6: records how it was shut down
+#[derive(Debug, Default)]
+pub struct ShutdownComponent {
+    /// Kind of shutdown received by `before_shutdown` (if any)
+    pub shutdown: Mutex<Option<Shutdown>>,
+}
+
+impl Component<ExampleApp> for ShutdownComponent {
+    fn id(&self) -> component::Id {
+        component::Id::new("component::ShutdownComponent")
+    }
+
+    fn version(&self) -> Version {
+        Version::parse(env!("CARGO_PKG_VERSION")).unwrap()
+    }
+
+    fn before_shutdown(&self, kind: Shutdown) -> Result<(), FrameworkError> {
+        *self.shutdown.lock().unwrap() = Some(kind);
+        Ok(())
+    }
+}
+
+/// Example component #7: fails to shut down
+#[derive(Debug, Default)]
+pub struct FailShutdownComponent {}
+
+impl Component<ExampleApp> for FailShutdownComponent {
+    fn id(&self) -> component::Id {
+        component::Id::new("component::FailShutdownComponent")
+    }
+
+    fn version(&self) -> Version {
+        Version::parse(env!("CARGO_PKG_VERSION")).unwrap()
+    }
+
+    fn before_shutdown(&self, _kind: Shutdown) -> Result<(), FrameworkError> {
+        fail!(ComponentError, "shutdown failed");
+    }
+}
+
+/// Example component #8: also fails to shut down
+#[derive(Debug, Default)]
+pub struct FailShutdownComponent2 {}
+
+impl Component<ExampleApp> for FailShutdownComponent2 {
+    fn id(&self) -> component::Id {
+        component::Id::new("component::FailShutdownComponent2")
+    }
+
+    fn version(&self) -> Version {
+        Version::parse(env!("CARGO_PKG_VERSION")).unwrap()
+    }
+
+    fn before_shutdown(&self, _kind: Shutdown) -> Result<(), FrameworkError> {
+        fail!(ComponentError, "shutdown failed again");
+    }
+}
+
+/// Example component #9: optionally depends on `ReloadComponent`, and
+/// requires a compatible version of `BazComponent`
+#[derive(Component, Debug, Default)]
+#[component(inject = "init_baz(component::BazComponent >=0.6, <2)")]
+#[component(inject = "init_reload(optional component::ReloadComponent)")]
+pub struct OptionalDepsComponent {
+    /// Did we get a callback that `Baz` has been initialized?
+    pub baz_initialized: bool,
+
+    /// Did we get a callback that `Reload` has been initialized?
+    pub reload_initialized: bool,
+}
+
+impl OptionalDepsComponent {
+    /// Callback run after `BazComponent` has been initialized
+    pub fn init_baz(&mut self, _baz: &BazComponent) -> Result<(), FrameworkError> {
+        self.baz_initialized = true;
+        Ok(())
+    }
+
+    /// Callback run after `ReloadComponent` has been initialized
+    pub fn init_reload(&mut self, _reload: &ReloadComponent) -> Result<(), FrameworkError> {
+        self.reload_initialized = true;
+        Ok(())
+    }
+}
+
+/// Example component #10: requires an unreleased version of `BazComponent`
+#[derive(Component, Debug, Default)]
+#[component(inject = "init_baz(component::BazComponent >=100)")]
+pub struct FutureBazComponent {}
+
+impl FutureBazComponent {
+    /// Callback run after `BazComponent` has been initialized
+    pub fn init_baz(&mut self, _baz: &BazComponent) -> Result<(), FrameworkError> {
+        Ok(())
+    }
+}
+
+/// Example component #11: fails to initialize
+#[derive(Debug, Default)]
+pub struct FailConfigComponent {}
+
+impl Component<ExampleApp> for FailConfigComponent {
+    fn id(&self) -> component::Id {
+        component::Id::new("component::FailConfigComponent")
+    }
+
+    fn version(&self) -> Version {
+        Version::parse(env!("CARGO_PKG_VERSION")).unwrap()
+    }
+
+    fn after_config(&mut self, _config: &ExampleConfig) -> Result<(), FrameworkError> {
+        fail!(ConfigError, "after_config failed");
+    }
+}
+
+/// Define example components which depend on each other
+macro_rules! dependent_components {
+    ($($name:ident: [$($dep:ident),*];)+) => {
+        $(
+            #[derive(Debug, Default)]
+            pub struct $name {}
+
+            impl Component<ExampleApp> for $name {
+                fn id(&self) -> component::Id {
+                    component::Id::new(concat!("component::", stringify!($name)))
+                }
+
+                fn version(&self) -> Version {
+                    Version::parse(env!("CARGO_PKG_VERSION")).unwrap()
+                }
+
+                fn dependencies(&self) -> slice::Iter<'_, component::Id> {
+                    const DEPENDENCIES: &[component::Id] = &[
+                        $(component::Id::new(concat!("component::", stringify!($dep)))),*
+                    ];
+
+                    DEPENDENCIES.iter()
+                }
+            }
+        )+
+    };
+}
+
+dependent_components! {
+    ChainA: [ChainB];
+    ChainB: [ChainC];
+    ChainC: [];
+    CycleA: [CycleB];
+    CycleB: [CycleC];
+    CycleC: [CycleA];
+    Orphan: [Missing];
+}
+
 fn init_components() -> Vec<Box<dyn Component<ExampleApp>>> {
     let mut foobar = FoobarComponent::default();
     foobar.set_state("original foobar state");
@@ -108,10 +316,9 @@ fn duplicate_component_registration() {
 
     let err = registry.register(components).err().unwrap();
     assert_eq!(*err.kind(), ComponentError);
-    assert_eq!(registry.len(), 1);
 
-    let foobar = registry.get_by_id(FOOBAR_COMPONENT_ID).unwrap();
-    assert_eq!(foobar.id(), FOOBAR_COMPONENT_ID);
+    // The batch is checked before any of it is registered
+    assert!(registry.is_empty());
 }
 
 #[test]
@@ -148,3 +355,433 @@ fn dependency_injection() {
     let quux = registry.get_downcast_ref::<QuuxComponent>().unwrap();
     assert_eq!(quux.foobar_state.as_ref().unwrap(), "original foobar state");
 }
+
+#[test]
+fn dependency_order() {
+    let components: Vec<Box<dyn Component<ExampleApp>>> = vec![
+        Box::new(ChainA::default()),
+        Box::new(BazComponent::default()),
+        Box::new(ChainB::default()),
+        Box::new(ChainC::default()),
+    ];
+
+    let mut registry = component::Registry::default();
+    registry.register(components).unwrap();
+
+    let ids = registry
+        .iter()
+        .map(|component| component.id().as_ref().to_owned())
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        ids,
+        [
+            "component::BazComponent",
+            "component::ChainC",
+            "component::ChainB",
+            "component::ChainA",
+        ]
+    );
+}
+
+#[test]
+fn dependency_cycle() {
+    let components: Vec<Box<dyn Component<ExampleApp>>> = vec![
+        Box::new(FoobarComponent::default()),
+        Box::new(CycleA::default()),
+        Box::new(CycleB::default()),
+        Box::new(CycleC::default()),
+    ];
+
+    let mut registry = component::Registry::default();
+    let err = registry.register(components).err().unwrap();
+    assert_eq!(*err.kind(), ComponentError);
+    assert_eq!(
+        err.to_string(),
+        "component error: dependency cycle: component::CycleA -> component::CycleB -> \
+         component::CycleC -> component::CycleA"
+    );
+    assert!(registry.is_empty());
+}
+
+#[test]
+fn unregistered_dependency() {
+    let components: Vec<Box<dyn Component<ExampleApp>>> = vec![Box::new(Orphan::default())];
+
+    let mut registry = component::Registry::default();
+    let err = registry.register(components).err().unwrap();
+    assert_eq!(*err.kind(), ComponentError);
+    assert_eq!(
+        err.to_string(),
+        "component error: component::Orphan depends on unregistered component: component::Missing"
+    );
+}
+
+#[test]
+fn optional_dependencies() {
+    let components: Vec<Box<dyn Component<ExampleApp>>> = vec![
+        Box::new(OptionalDepsComponent::default()),
+        Box::new(BazComponent::default()),
+    ];
+
+    let mut registry = component::Registry::default();
+    registry.register(components).unwrap();
+    registry.after_config(&ExampleConfig::default()).unwrap();
+
+    let component = registry
+        .get_downcast_ref::<OptionalDepsComponent>()
+        .unwrap();
+    assert!(component.baz_initialized);
+    assert!(!component.reload_initialized);
+
+    let components: Vec<Box<dyn Component<ExampleApp>>> = vec![
+        Box::new(OptionalDepsComponent::default()),
+        Box::new(BazComponent::default()),
+        Box::new(ReloadComponent::default()),
+    ];
+
+    let mut registry = component::Registry::default();
+    registry.register(components).unwrap();
+    registry.after_config(&ExampleConfig::default()).unwrap();
+
+    let component = registry
+        .get_downcast_ref::<OptionalDepsComponent>()
+        .unwrap();
+    assert!(component.baz_initialized);
+    assert!(component.reload_initialized);
+
+    // Optional dependencies are started first
+    let ids = registry
+        .iter()
+        .map(|component| component.id().as_ref().to_owned())
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        ids,
+        [
+            "component::BazComponent",
+            "component::ReloadComponent",
+            "component::OptionalDepsComponent",
+        ]
+    );
+
+    let reload = registry
+        .get_handle_by_id(component::Id::new("component::ReloadComponent"))
+        .unwrap();
+    assert!(registry.remove(reload).is_err());
+}
+
+#[test]
+fn version_requirements() {
+    let components: Vec<Box<dyn Component<ExampleApp>>> = vec![
+        Box::new(BazComponent::default()),
+        Box::new(FutureBazComponent::default()),
+    ];
+
+    let mut registry = component::Registry::default();
+    let err = registry.register(components).err().unwrap();
+    assert_eq!(*err.kind(), ComponentError);
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "component error: component::FutureBazComponent requires \
+             component::BazComponent >=100 (found {})",
+            env!("CARGO_PKG_VERSION")
+        )
+    );
+    assert!(registry.is_empty());
+}
+
+#[test]
+fn runtime_registration() {
+    let mut foobar = FoobarComponent::default();
+    foobar.set_state("original foobar state");
+
+    let components: Vec<Box<dyn Component<ExampleApp>>> =
+        vec![Box::new(foobar), Box::new(BazComponent::default())];
+
+    let mut registry = component::Registry::default();
+    registry.register(components).unwrap();
+    registry.after_config(&ExampleConfig::default()).unwrap();
+
+    let components: Vec<Box<dyn Component<ExampleApp>>> = vec![
+        Box::new(QuuxComponent::default()),
+        Box::new(ReloadComponent::default()),
+    ];
+
+    registry
+        .register_after_config(components, &ExampleConfig::default())
+        .unwrap();
+
+    assert_eq!(registry.len(), 4);
+
+    let quux = registry.get_downcast_ref::<QuuxComponent>().unwrap();
+    assert_eq!(quux.foobar_state.as_ref().unwrap(), "original foobar state");
+    assert!(quux.baz_initialized);
+
+    // Existing components don't receive their dependencies again
+    let foobar = registry.get_downcast_ref::<FoobarComponent>().unwrap();
+    assert_eq!(foobar.state.as_ref().unwrap(), "hijacked!");
+}
+
+#[test]
+fn runtime_registration_rollback() {
+    let mut registry = component::Registry::default();
+    registry.register(init_components()).unwrap();
+    registry.after_config(&ExampleConfig::default()).unwrap();
+
+    // Duplicates partway through the batch
+    let components: Vec<Box<dyn Component<ExampleApp>>> = vec![
+        Box::new(ReloadComponent::default()),
+        Box::new(BazComponent::default()),
+    ];
+
+    let err = registry
+        .register_after_config(components, &ExampleConfig::default())
+        .err()
+        .unwrap();
+
+    assert_eq!(
+        err.to_string(),
+        "component error: duplicate component registration: component::BazComponent"
+    );
+    assert_eq!(registry.len(), 3);
+
+    // Failing `after_config` callbacks
+    let components: Vec<Box<dyn Component<ExampleApp>>> = vec![
+        Box::new(ReloadComponent::default()),
+        Box::new(FailConfigComponent::default()),
+    ];
+
+    let err = registry
+        .register_after_config(components, &ExampleConfig::default())
+        .err()
+        .unwrap();
+
+    assert_eq!(*err.kind(), ConfigError);
+    assert_eq!(registry.len(), 3);
+    assert!(registry.get_downcast_ref::<ReloadComponent>().is_none());
+
+    // The new components can be registered again afterwards
+    registry
+        .register_after_config(
+            vec![Box::new(ReloadComponent::default()) as Box<dyn Component<ExampleApp>>],
+            &ExampleConfig::default(),
+        )
+        .unwrap();
+
+    assert_eq!(registry.len(), 4);
+    assert!(registry.get_downcast_ref::<ReloadComponent>().is_some());
+}
+
+#[test]
+fn component_removal() {
+    let mut components = init_components();
+    components.push(Box::new(ShutdownComponent::default()));
+
+    let mut registry = component::Registry::default();
+    registry.register(components).unwrap();
+    registry.after_config(&ExampleConfig::default()).unwrap();
+
+    let foobar = registry.get_handle_by_id(FOOBAR_COMPONENT_ID).unwrap();
+    let quux = registry.get_handle_by_id(QUUX_COMPONENT_ID).unwrap();
+    let shutdown = registry
+        .get_handle_by_id(component::Id::new("component::ShutdownComponent"))
+        .unwrap();
+
+    let err = registry.remove(foobar).err().unwrap();
+    assert_eq!(*err.kind(), ComponentError);
+    assert_eq!(
+        err.to_string(),
+        "component error: can't remove component::FoobarComponent: \
+         depended on by component::QuuxComponent"
+    );
+
+    registry.remove(quux).unwrap();
+    registry.remove(foobar).unwrap();
+    assert_eq!(registry.len(), 2);
+
+    let removed = registry.remove(shutdown).unwrap();
+    let removed = removed
+        .as_any()
+        .downcast_ref::<ShutdownComponent>()
+        .unwrap();
+    assert_eq!(*removed.shutdown.lock().unwrap(), Some(Shutdown::Graceful));
+
+    // Handles to removed components stay invalid after new registrations
+    registry
+        .register_after_config(
+            vec![Box::new(FoobarComponent::default()) as Box<dyn Component<ExampleApp>>],
+            &ExampleConfig::default(),
+        )
+        .unwrap();
+
+    assert!(registry.get(foobar).is_none());
+    assert!(registry.get(quux).is_none());
+    assert!(registry.remove(quux).is_err());
+
+    let baz = registry.get_handle_by_id(BAZ_COMPONENT_ID).unwrap();
+    assert_eq!(registry.get(baz).unwrap().id(), BAZ_COMPONENT_ID);
+    assert!(registry.get_downcast_ref::<FoobarComponent>().is_some());
+}
+
+#[test]
+fn stale_handles() {
+    let components: Vec<Box<dyn Component<ExampleApp>>> = vec![
+        Box::new(FoobarComponent::default()),
+        Box::new(BazComponent::default()),
+    ];
+
+    let mut registry = component::Registry::default();
+    registry.register(components).unwrap();
+
+    let foobar = registry.get_handle_by_id(FOOBAR_COMPONENT_ID).unwrap();
+    let baz = registry.get_handle_by_id(BAZ_COMPONENT_ID).unwrap();
+    registry.remove(foobar).unwrap();
+
+    // `ReloadComponent` reuses the arena slot of `FoobarComponent`
+    registry
+        .register_after_config(
+            vec![Box::new(ReloadComponent::default()) as Box<dyn Component<ExampleApp>>],
+            &ExampleConfig::default(),
+        )
+        .unwrap();
+
+    let reload = registry
+        .get_handle_by_id(component::Id::new("component::ReloadComponent"))
+        .unwrap();
+
+    assert!(registry.get(foobar).is_none());
+    assert!(registry.get_mut(foobar).is_none());
+    assert_eq!(registry.get(baz).unwrap().id(), BAZ_COMPONENT_ID);
+    assert_eq!(
+        registry.get(reload).unwrap().id().as_ref(),
+        "component::ReloadComponent"
+    );
+}
+
+#[test]
+fn typed_handles() {
+    let mut registry = component::Registry::default();
+    registry.register(init_components()).unwrap();
+
+    let foobar = registry.get_typed_handle::<FoobarComponent>().unwrap();
+    assert_eq!(foobar.id(), FOOBAR_COMPONENT_ID);
+
+    registry
+        .get_typed_mut(foobar)
+        .unwrap()
+        .set_state("mutated!");
+
+    let foobar_ref = registry.get_typed(foobar).unwrap();
+    assert_eq!(foobar_ref.state.as_ref().unwrap(), "mutated!");
+
+    let quux = registry.get_typed_handle::<QuuxComponent>().unwrap();
+    registry.remove(quux.handle()).unwrap();
+    assert!(registry.get_typed(quux).is_none());
+    assert!(registry.get_typed_handle::<QuuxComponent>().is_none());
+}
+
+#[test]
+fn config_reload() {
+    let state = application::State::<ExampleApp>::default();
+    let components: Vec<Box<dyn Component<ExampleApp>>> = vec![
+        Box::new(FoobarComponent::default()),
+        Box::new(ReloadComponent::default()),
+    ];
+    state.components_mut().register(components).unwrap();
+
+    let cell = CfgCell::default();
+    cell.set_once(ExampleConfig { value: 42 });
+
+    let mut loader = Loader::new();
+    loader.profile(Some("reloaded".to_owned()));
+
+    let config = cell.reload(&loader, &ExampleCommand {}, &state).unwrap();
+    assert_eq!(config.value, 0);
+    assert_eq!(cell.read().value, 0);
+    assert_eq!(state.config_profile().as_deref(), Some("reloaded"));
+
+    let registry = state.components();
+    let reload = registry.get_downcast_ref::<ReloadComponent>().unwrap();
+    assert_eq!(reload.value, 0);
+}
+
+#[test]
+fn config_env_overlay_is_opt_in() {
+    std::env::set_var("ABSCISSA_CORE__VALUE", "42");
+
+    let loader = ExampleApp::default().config_loader(&ExampleCommand {});
+    let config: ExampleConfig = loader.load().unwrap();
+    assert_eq!(config.value, 0);
+
+    let mut loader = loader;
+    loader.env_prefix("ABSCISSA_CORE");
+    let config: ExampleConfig = loader.load().unwrap();
+    assert_eq!(config.value, 42);
+}
+
+#[test]
+fn config_reload_rollback() {
+    let state = application::State::<ExampleApp>::default();
+    let components: Vec<Box<dyn Component<ExampleApp>>> = vec![
+        Box::new(ReloadComponent::default()),
+        Box::new(RejectReloadComponent::default()),
+    ];
+    state.components_mut().register(components).unwrap();
+
+    let old = ExampleConfig { value: 1 };
+    let new = ExampleConfig { value: 2 };
+
+    let err = state
+        .components_mut()
+        .reload_config(&old, &new)
+        .err()
+        .unwrap();
+    assert_eq!(*err.kind(), ConfigError);
+
+    // Components which accepted the new config should be rolled back
+    let registry = state.components();
+    let reload = registry.get_downcast_ref::<ReloadComponent>().unwrap();
+    assert_eq!(reload.value, 1);
+    drop(registry);
+
+    // The config cell should retain the old config
+    let cell = CfgCell::default();
+    cell.set_once(old);
+    assert!(cell
+        .reload(&Loader::new(), &ExampleCommand {}, &state)
+        .is_err());
+    assert_eq!(cell.read().value, 1);
+}
+
+#[test]
+fn shutdown_errors() {
+    let mut registry = component::Registry::default();
+    let components: Vec<Box<dyn Component<ExampleApp>>> = vec![
+        Box::new(ShutdownComponent::default()),
+        Box::new(FailShutdownComponent::default()),
+        Box::new(FailShutdownComponent2::default()),
+    ];
+    registry.register(components).unwrap();
+
+    let err = registry
+        .shutdown(&ExampleApp::default(), Shutdown::Graceful)
+        .err()
+        .unwrap();
+
+    assert_eq!(*err.kind(), ComponentError);
+    assert_eq!(
+        err.to_string(),
+        "component error: 2 component(s) failed to shut down:\n\
+         - component::FailShutdownComponent2: component error: shutdown failed again\n\
+         - component::FailShutdownComponent: component error: shutdown failed"
+    );
+
+    // Components are still shut down after others fail
+    let component = registry.get_downcast_ref::<ShutdownComponent>().unwrap();
+    assert_eq!(
+        *component.shutdown.lock().unwrap(),
+        Some(Shutdown::Graceful)
+    );
+}
@@ -0,0 +1,189 @@
+//! Tests for Abscissa's crash handling.
+//!
+//! These run this test binary as a child process which boots an application
+//! that panics (see the `child` test), and check the crash report it writes.
+
+#![cfg(feature = "testing")]
+
+mod support;
+
+use self::support::{expect_report, report, ChildApp, ChildCommand, ChildConfig};
+use abscissa_core::{
+    application::{AppCell, CRASH_EXIT_CODE},
+    clap::Parser,
+    component, fs,
+    testing::{prelude::*, process::Process},
+    Command, Component, Configurable, FrameworkError, Runnable, Shutdown, Version,
+};
+use std::{
+    env,
+    path::{Path, PathBuf},
+    process,
+};
+
+/// Application run by the child process
+type CrashApp = ChildApp<CrashCommand>;
+
+/// Application state of the child process
+static APP: AppCell<CrashApp> = AppCell::new();
+
+/// Command which panics
+#[derive(Command, Debug, Parser)]
+#[command(version = "1.2.3")]
+pub struct CrashCommand {
+    /// Secret which shouldn't appear in crash reports
+    #[arg(long)]
+    token: Option<String>,
+
+    /// Panic while registering components
+    #[arg(long)]
+    panic_on_boot: bool,
+}
+
+impl Configurable<ChildConfig> for CrashCommand {
+    fn config_path(&self) -> Option<PathBuf> {
+        None
+    }
+}
+
+impl ChildCommand for CrashCommand {
+    fn components(&self) -> Vec<Box<dyn Component<CrashApp>>> {
+        // Panic after the terminal has replaced the panic hook
+        if self.panic_on_boot {
+            panic!("something went wrong while booting");
+        }
+
+        vec![Box::new(Reporter {})]
+    }
+}
+
+impl Runnable for CrashCommand {
+    fn run(&self) {
+        panic!("something went wrong");
+    }
+}
+
+/// Component which reports when it's shut down
+#[derive(Debug)]
+pub struct Reporter {}
+
+impl Component<CrashApp> for Reporter {
+    fn id(&self) -> component::Id {
+        component::Id::new("crash::Reporter")
+    }
+
+    fn version(&self) -> Version {
+        Version::parse("0.4.2").unwrap()
+    }
+
+    fn before_shutdown(&self, kind: Shutdown) -> Result<(), FrameworkError> {
+        report(&format!("before_shutdown: {:?}", kind));
+        Ok(())
+    }
+}
+
+/// Run the application when invoked as a child process (and do nothing
+/// otherwise).
+#[test]
+fn child() {
+    support::run_child(&APP, "crash-app");
+}
+
+/// Run this test binary as a child process running the application with the
+/// given arguments, writing crash reports into the given directory.
+fn spawn_child<'r>(runner: &'r mut CmdRunner, reports_dir: &Path) -> Process<'r> {
+    runner
+        .env(support::CHILD_REPORTS_ENV, reports_dir)
+        .capture_stderr();
+
+    runner.run()
+}
+
+/// Read the only crash report in the given directory, and remove it.
+fn read_report(reports_dir: &Path) -> String {
+    let reports = fs::read_dir(reports_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect::<Vec<_>>();
+
+    assert_eq!(reports.len(), 1);
+    let report = fs::read_to_string(&reports[0]).unwrap();
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir_mode = fs::metadata(reports_dir).unwrap().permissions().mode();
+        assert_eq!(dir_mode & 0o777, 0o700);
+
+        let report_mode = fs::metadata(&reports[0]).unwrap().permissions().mode();
+        assert_eq!(report_mode & 0o777, 0o600);
+    }
+
+    fs::remove_dir_all(reports_dir).unwrap();
+    report
+}
+
+/// Check that the given crash report contains all the expected lines.
+fn assert_report_contains(report: &str, expected_lines: &[&str]) {
+    for expected in expected_lines {
+        assert!(
+            report.contains(expected),
+            "missing {:?}:\n{}",
+            expected,
+            report
+        );
+    }
+}
+
+#[test]
+fn crash_report() {
+    let reports_dir = env::temp_dir().join(format!("abscissa-crash-{}", process::id()));
+
+    let mut runner = support::child_runner("--token=hunter2");
+    let mut process = spawn_child(&mut runner, &reports_dir);
+    expect_report(&mut process, "before_shutdown: Crash");
+
+    let status = process.wait().unwrap();
+    assert_eq!(status.code(), CRASH_EXIT_CODE);
+
+    let report = read_report(&reports_dir);
+    assert!(!report.contains("hunter2"), "secret in report:\n{}", report);
+
+    assert_report_contains(
+        &report,
+        &[
+            "abscissa_core crash report",
+            "version: 1.2.3",
+            "command line: [\"crash-app\", \"--token=<redacted>\"]",
+            "message: something went wrong",
+            "location: core/tests/crash.rs:",
+            "- crash::Reporter 0.4.2",
+            "backtrace:",
+        ],
+    );
+}
+
+#[test]
+fn boot_crash_report() {
+    let reports_dir = env::temp_dir().join(format!("abscissa-boot-crash-{}", process::id()));
+
+    let mut runner = support::child_runner("--panic-on-boot --token hunter2");
+    let process = spawn_child(&mut runner, &reports_dir);
+
+    let status = process.wait().unwrap();
+    assert_eq!(status.code(), CRASH_EXIT_CODE);
+
+    let report = read_report(&reports_dir);
+    assert!(!report.contains("hunter2"), "secret in report:\n{}", report);
+
+    assert_report_contains(
+        &report,
+        &[
+            "abscissa_core crash report",
+            "command line: [\"crash-app\", \"--panic-on-boot\", \"--token\", \"<redacted>\"]",
+            "message: something went wrong while booting",
+            "<application not booted>",
+        ],
+    );
+}
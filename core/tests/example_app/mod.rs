@@ -8,7 +8,10 @@ use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
-pub struct ExampleConfig {}
+pub struct ExampleConfig {
+    #[serde(default)]
+    pub value: u32,
+}
 
 #[derive(Command, Debug, Parser)]
 pub struct ExampleCommand {}
@@ -0,0 +1,92 @@
+//! Tests for the exit codes of applications whose commands return errors.
+//!
+//! These run this test binary as a child process which boots an application
+//! whose command fails (see the `child` test), and check how it exited.
+
+#![cfg(feature = "testing")]
+
+mod support;
+
+use self::support::{expect_report, report, ChildApp, ChildCommand, ChildConfig};
+use abscissa_core::{
+    application::AppCell, clap::Parser, component, fail, format_err, Command, Component,
+    Configurable, FrameworkError, FrameworkErrorKind::ConfigError, Shutdown, TryRunnable, Version,
+};
+use std::{io::Read, path::PathBuf};
+
+/// Application run by the child process
+type FailingApp = ChildApp<FailingCommand>;
+
+/// Application state of the child process
+static APP: AppCell<FailingApp> = AppCell::new();
+
+/// Command which fails with a configuration error
+#[derive(Command, Debug, Parser)]
+pub struct FailingCommand {}
+
+impl Configurable<ChildConfig> for FailingCommand {
+    fn config_path(&self) -> Option<PathBuf> {
+        None
+    }
+}
+
+impl ChildCommand for FailingCommand {
+    fn components(&self) -> Vec<Box<dyn Component<FailingApp>>> {
+        vec![Box::new(Reporter {})]
+    }
+}
+
+impl TryRunnable for FailingCommand {
+    type Error = FrameworkError;
+
+    fn try_run(&self) -> Result<(), FrameworkError> {
+        fail!(ConfigError, "missing setting `foo`")
+    }
+}
+
+/// Component which reports when it's shut down
+#[derive(Debug)]
+pub struct Reporter {}
+
+impl Component<FailingApp> for Reporter {
+    fn id(&self) -> component::Id {
+        component::Id::new("exit_code::Reporter")
+    }
+
+    fn version(&self) -> Version {
+        Version::parse("0.0.0").unwrap()
+    }
+
+    fn before_shutdown(&self, kind: Shutdown) -> Result<(), FrameworkError> {
+        report(&format!("before_shutdown: {:?}", kind));
+        Ok(())
+    }
+}
+
+/// Run the application when invoked as a child process (and do nothing
+/// otherwise).
+#[test]
+fn child() {
+    support::run_child(&APP, "failing-app");
+}
+
+#[test]
+fn command_error_exit_code() {
+    let mut runner = support::child_runner("");
+    runner.capture_stderr();
+
+    let mut process = runner.run();
+    expect_report(&mut process, "before_shutdown: Graceful");
+
+    let mut stderr = String::new();
+    process.stderr().read_to_string(&mut stderr).unwrap();
+    assert!(
+        stderr.contains("fatal error: config error: missing setting `foo`"),
+        "unexpected stderr: {}",
+        stderr
+    );
+
+    // `EX_CONFIG` from `sysexits.h`
+    let status = process.wait().unwrap();
+    assert_eq!(status.code(), 78);
+}
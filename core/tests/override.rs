@@ -0,0 +1,97 @@
+//! Tests for `#[derive(Override)]`
+
+use abscissa_core::{clap::Parser, config::Override, Command, Runnable};
+use serde::Deserialize;
+
+/// Example configuration
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+pub struct ExampleConfig {
+    pub server: ServerConfig,
+    pub verbose: bool,
+    pub peers: Vec<String>,
+}
+
+/// Example nested configuration section
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+pub struct ServerConfig {
+    pub host: String,
+    pub port: Option<u16>,
+}
+
+/// Example command which overrides configuration values
+#[derive(Command, Debug, Override, Parser)]
+#[config(type = "ExampleConfig")]
+pub struct ExampleCommand {
+    /// Host to listen on
+    #[arg(long)]
+    #[config(path = "server.host")]
+    host: Option<String>,
+
+    /// Port to listen on
+    #[arg(long)]
+    #[config(path = "server.port")]
+    port: Option<u16>,
+
+    /// Enable verbose output
+    #[arg(long)]
+    #[config(path = "verbose")]
+    verbose: bool,
+
+    /// Peers to connect to
+    #[arg(long)]
+    #[config(path = "peers")]
+    peer: Vec<String>,
+
+    /// Not a configuration option
+    #[arg(long)]
+    dry_run: bool,
+}
+
+impl Runnable for ExampleCommand {
+    fn run(&self) {
+        unimplemented!();
+    }
+}
+
+fn example_config() -> ExampleConfig {
+    ExampleConfig {
+        server: ServerConfig {
+            host: "localhost".to_owned(),
+            port: Some(80),
+        },
+        verbose: false,
+        peers: vec!["peer1".to_owned()],
+    }
+}
+
+#[test]
+fn override_passed_values() {
+    let cmd = ExampleCommand::parse_from([
+        "example",
+        "--host",
+        "0.0.0.0",
+        "--port",
+        "8080",
+        "--verbose",
+        "--peer",
+        "peer2",
+        "--peer",
+        "peer3",
+    ]);
+
+    let config = cmd.override_config(example_config()).unwrap();
+
+    assert_eq!(config.server.host, "0.0.0.0");
+    assert_eq!(config.server.port, Some(8080));
+    assert!(config.verbose);
+    assert_eq!(config.peers, ["peer2", "peer3"]);
+}
+
+#[test]
+fn keep_unpassed_values() {
+    let cmd = ExampleCommand::parse_from(["example", "--dry-run"]);
+    assert_eq!(
+        cmd.override_config(example_config()).unwrap(),
+        example_config()
+    );
+}
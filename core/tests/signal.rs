@@ -0,0 +1,359 @@
+//! Tests for Abscissa's signal handling.
+//!
+//! These run this test binary as a child process which boots an application
+//! (see the `child` test), send it signals, and check how it shut down.
+
+#![cfg(all(unix, feature = "signals", feature = "testing"))]
+
+mod support;
+
+use self::support::{expect_report, report, ChildApp, ChildCommand, ChildConfig};
+use abscissa_core::{
+    application::AppCell,
+    clap::Parser,
+    component, fail, format_err,
+    signal::Signal,
+    testing::{prelude::*, process::Process},
+    thread, Application, Command, Component, Configurable, FrameworkError,
+    FrameworkErrorKind::ComponentError,
+    Runnable, Shutdown, Version,
+};
+use std::{io::Read, path::PathBuf, time::Duration};
+
+/// Application run by the child process
+type SignalApp = ChildApp<SignalCommand>;
+
+/// Application state of the child process
+static APP: AppCell<SignalApp> = AppCell::new();
+
+/// Command which waits until the application is shut down by a signal
+#[derive(Command, Debug, Parser)]
+pub struct SignalCommand {
+    /// Take a long time to shut down gracefully
+    #[arg(long)]
+    slow: bool,
+
+    /// Time limit for each component's graceful shutdown (in milliseconds)
+    #[arg(long)]
+    deadline_ms: Option<u64>,
+
+    /// Spawn a managed thread which ignores its kill switch
+    #[arg(long)]
+    stubborn_thread: bool,
+
+    /// Time to wait for managed threads to exit (in milliseconds)
+    #[arg(long)]
+    join_timeout_ms: Option<u64>,
+
+    /// Spawn a managed thread which panics
+    #[arg(long)]
+    panicking_thread: bool,
+
+    /// Panic while shutting down gracefully
+    #[arg(long)]
+    panicking_shutdown: bool,
+
+    /// Keep the component registry locked for writing
+    #[arg(long)]
+    locked_registry: bool,
+}
+
+impl Configurable<ChildConfig> for SignalCommand {
+    fn config_path(&self) -> Option<PathBuf> {
+        None
+    }
+}
+
+impl ChildCommand for SignalCommand {
+    fn components(&self) -> Vec<Box<dyn Component<SignalApp>>> {
+        vec![
+            Box::new(Observer {}),
+            Box::new(Reporter {
+                slow: self.slow,
+                panicking: self.panicking_shutdown,
+            }),
+        ]
+    }
+
+    fn graceful_deadline(&self) -> Option<Duration> {
+        self.deadline_ms.map(Duration::from_millis)
+    }
+
+    fn thread_join_timeout(&self) -> Duration {
+        self.join_timeout_ms
+            .map(Duration::from_millis)
+            .unwrap_or_else(|| Duration::from_secs(10))
+    }
+}
+
+impl Runnable for SignalCommand {
+    fn run(&self) {
+        if self.stubborn_thread {
+            APP.state()
+                .threads_mut()
+                .spawn(thread::Name::new("stubborn").unwrap(), || loop {
+                    std::thread::sleep(Duration::from_millis(100));
+                })
+                .unwrap();
+        }
+
+        if self.panicking_thread {
+            APP.state()
+                .threads_mut()
+                .spawn(thread::Name::new("panicking").unwrap(), || {
+                    panic!("managed thread panicked")
+                })
+                .unwrap();
+        }
+
+        // Keep the registry locked until the process exits
+        let _components = if self.locked_registry {
+            Some(APP.state().components_mut())
+        } else {
+            None
+        };
+
+        report("ready");
+
+        loop {
+            std::thread::sleep(Duration::from_millis(100));
+        }
+    }
+}
+
+/// Component which reports the signals it handles and when it's shut down
+#[derive(Debug)]
+pub struct Reporter {
+    slow: bool,
+    panicking: bool,
+}
+
+impl Component<SignalApp> for Reporter {
+    fn id(&self) -> component::Id {
+        component::Id::new("signal::Reporter")
+    }
+
+    fn version(&self) -> Version {
+        Version::parse("0.0.0").unwrap()
+    }
+
+    fn signals(&self) -> Vec<Signal> {
+        vec![Signal::Hangup, Signal::User1]
+    }
+
+    fn on_signal(&self, signal: Signal) -> Result<(), FrameworkError> {
+        report(&format!("on_signal: {}", signal));
+
+        if signal == Signal::User1 {
+            fail!(ComponentError, "can't handle {}", signal);
+        }
+
+        Ok(())
+    }
+
+    fn on_config_reload(
+        &mut self,
+        _old: &ChildConfig,
+        _new: &ChildConfig,
+    ) -> Result<(), FrameworkError> {
+        report("on_config_reload");
+        Ok(())
+    }
+
+    fn before_shutdown(&self, kind: Shutdown) -> Result<(), FrameworkError> {
+        report(&format!("before_shutdown: {:?}", kind));
+
+        if self.slow && kind == Shutdown::Graceful {
+            std::thread::sleep(Duration::from_secs(60));
+        }
+
+        if self.panicking && kind == Shutdown::Graceful {
+            panic!("component panicked");
+        }
+
+        Ok(())
+    }
+}
+
+/// Component which is shut down after `Reporter`, reporting how
+#[derive(Debug)]
+pub struct Observer {}
+
+impl Component<SignalApp> for Observer {
+    fn id(&self) -> component::Id {
+        component::Id::new("signal::Observer")
+    }
+
+    fn version(&self) -> Version {
+        Version::parse("0.0.0").unwrap()
+    }
+
+    fn before_shutdown(&self, kind: Shutdown) -> Result<(), FrameworkError> {
+        report(&format!("observer: before_shutdown: {:?}", kind));
+        Ok(())
+    }
+}
+
+/// Run the application when invoked as a child process (and do nothing
+/// otherwise).
+#[test]
+fn child() {
+    support::run_child(&APP, "signal-app");
+}
+
+/// Run this test binary as a child process running the application with the
+/// given arguments, and wait until it's ready.
+fn spawn_child<'r>(runner: &'r mut CmdRunner) -> Process<'r> {
+    let mut process = runner.run();
+    expect_report(&mut process, "ready");
+    process
+}
+
+#[test]
+fn graceful_shutdown() {
+    let mut runner = support::child_runner("");
+    let mut process = spawn_child(&mut runner);
+
+    process.signal(Signal::Terminate).unwrap();
+    expect_report(&mut process, "before_shutdown: Graceful");
+
+    let status = process.wait().unwrap();
+    assert_eq!(status.code(), Signal::Terminate.exit_code());
+}
+
+#[test]
+fn second_signal_forces_shutdown() {
+    let mut runner = support::child_runner("--slow");
+    let mut process = spawn_child(&mut runner);
+
+    process.signal(Signal::Interrupt).unwrap();
+    expect_report(&mut process, "before_shutdown: Graceful");
+
+    // `Reporter` is abandoned, and the remaining components are forced
+    process.signal(Signal::Interrupt).unwrap();
+    expect_report(&mut process, "observer: before_shutdown: Forced");
+
+    let status = process.wait().unwrap();
+    assert_eq!(status.code(), Signal::Interrupt.exit_code());
+}
+
+#[test]
+fn second_signal_skips_locked_registry() {
+    let mut runner = support::child_runner("--locked-registry");
+    let process = spawn_child(&mut runner);
+
+    // The graceful shutdown waits for the registry to be unlocked, until
+    // it's escalated
+    process.signal(Signal::Interrupt).unwrap();
+    std::thread::sleep(Duration::from_millis(200));
+    process.signal(Signal::Interrupt).unwrap();
+
+    let status = process.wait().unwrap();
+    assert_eq!(status.code(), Signal::Interrupt.exit_code());
+}
+
+#[test]
+fn component_signal_hooks() {
+    let mut runner = support::child_runner("");
+    let mut process = spawn_child(&mut runner);
+
+    process.signal(Signal::User1).unwrap();
+    expect_report(&mut process, "on_signal: SIGUSR1");
+
+    // The error from the previous hook is logged, and the app keeps running.
+    // `SIGHUP` reloads the config before the hooks run.
+    process.signal(Signal::Hangup).unwrap();
+    expect_report(&mut process, "on_config_reload");
+    expect_report(&mut process, "on_signal: SIGHUP");
+
+    process.signal(Signal::Terminate).unwrap();
+    expect_report(&mut process, "before_shutdown: Graceful");
+
+    let status = process.wait().unwrap();
+    assert_eq!(status.code(), Signal::Terminate.exit_code());
+}
+
+#[test]
+fn shutdown_deadline_escalates() {
+    let mut runner = support::child_runner("--slow --deadline-ms 200");
+    let mut process = spawn_child(&mut runner);
+
+    process.signal(Signal::Terminate).unwrap();
+    expect_report(&mut process, "before_shutdown: Graceful");
+
+    // `Reporter` is abandoned, and the remaining components are forced
+    expect_report(&mut process, "observer: before_shutdown: Forced");
+
+    let status = process.wait().unwrap();
+    assert_eq!(status.code(), Signal::Terminate.exit_code());
+}
+
+#[test]
+fn stubborn_thread_fails_shutdown() {
+    let mut runner = support::child_runner("--stubborn-thread --join-timeout-ms 200");
+    runner.capture_stderr();
+
+    let mut process = spawn_child(&mut runner);
+
+    process.signal(Signal::Terminate).unwrap();
+    expect_report(&mut process, "before_shutdown: Graceful");
+
+    let mut stderr = String::new();
+    process.stderr().read_to_string(&mut stderr).unwrap();
+    assert!(
+        stderr.contains("threads didn't exit within 200ms: stubborn"),
+        "unexpected stderr: {}",
+        stderr
+    );
+
+    let status = process.wait().unwrap();
+    assert_eq!(status.code(), Signal::Terminate.exit_code());
+}
+
+#[test]
+fn panicked_thread_fails_shutdown() {
+    let mut runner = support::child_runner("--panicking-thread");
+    runner.capture_stderr();
+
+    let mut process = spawn_child(&mut runner);
+
+    // The panic doesn't crash the application, but is reported on shutdown
+    process.signal(Signal::Terminate).unwrap();
+    expect_report(&mut process, "before_shutdown: Graceful");
+
+    let mut stderr = String::new();
+    process.stderr().read_to_string(&mut stderr).unwrap();
+    assert!(
+        stderr.contains("threads panicked: panicking"),
+        "unexpected stderr: {}",
+        stderr
+    );
+
+    let status = process.wait().unwrap();
+    assert_eq!(status.code(), Signal::Terminate.exit_code());
+}
+
+#[test]
+fn panicked_component_fails_shutdown() {
+    let mut runner = support::child_runner("--panicking-shutdown --deadline-ms 5000");
+    runner.capture_stderr();
+
+    let mut process = spawn_child(&mut runner);
+
+    process.signal(Signal::Terminate).unwrap();
+    expect_report(&mut process, "before_shutdown: Graceful");
+
+    // The remaining components are still shut down gracefully
+    expect_report(&mut process, "observer: before_shutdown: Graceful");
+
+    let mut stderr = String::new();
+    process.stderr().read_to_string(&mut stderr).unwrap();
+    assert!(
+        stderr.contains("signal::Reporter: component error: panicked during shutdown"),
+        "unexpected stderr: {}",
+        stderr
+    );
+
+    let status = process.wait().unwrap();
+    assert_eq!(status.code(), Signal::Terminate.exit_code());
+}
@@ -0,0 +1,170 @@
+//! Harness for tests which run their own test binary as a child process
+//! booting an application (see [`run_child`]), and check how it behaves.
+
+// Not every test uses every part of the harness
+#![allow(dead_code)]
+
+use abscissa_core::{
+    application::{self, AppCell},
+    clap::Parser,
+    config::{self, CfgCell},
+    testing::{prelude::*, process::Process},
+    Application, Command, Component, Configurable, FrameworkError, Shutdown, StandardPaths,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    env,
+    fmt::{self, Debug},
+    io::{self, BufRead, Write},
+    path::PathBuf,
+    time::Duration,
+};
+
+/// Environment variable containing the arguments for the child application
+const CHILD_ARGS_ENV: &str = "ABSCISSA_TEST_CHILD_ARGS";
+
+/// Environment variable containing the crash reports directory of the child
+pub const CHILD_REPORTS_ENV: &str = "ABSCISSA_TEST_CRASH_REPORTS";
+
+/// Configuration of the child application
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct ChildConfig {}
+
+/// Command run by the child application
+pub trait ChildCommand:
+    Command + Configurable<ChildConfig> + Parser + Send + Sync + 'static
+{
+    /// Components registered after the framework's
+    fn components(&self) -> Vec<Box<dyn Component<ChildApp<Self>>>>;
+
+    /// Time limit for each component's graceful shutdown
+    fn graceful_deadline(&self) -> Option<Duration> {
+        Some(Duration::from_secs(30))
+    }
+
+    /// Time to wait for managed threads to exit
+    fn thread_join_timeout(&self) -> Duration {
+        Duration::from_secs(10)
+    }
+}
+
+/// Application run by the child process
+pub struct ChildApp<C: ChildCommand> {
+    config: CfgCell<ChildConfig>,
+    state: application::State<Self>,
+}
+
+impl<C: ChildCommand> Debug for ChildApp<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChildApp")
+            .field("config", &self.config)
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+impl<C: ChildCommand> Default for ChildApp<C> {
+    fn default() -> Self {
+        Self {
+            config: CfgCell::default(),
+            state: application::State::default(),
+        }
+    }
+}
+
+impl<C: ChildCommand> ChildApp<C> {
+    /// Get the command run by the application.
+    fn command(&self) -> &C {
+        self.state.command().expect("command not set")
+    }
+}
+
+impl<C: ChildCommand> Application for ChildApp<C> {
+    type Cmd = C;
+    type Cfg = ChildConfig;
+    type Paths = StandardPaths;
+
+    fn config(&self) -> config::Reader<ChildConfig> {
+        self.config.read()
+    }
+
+    fn state(&self) -> &application::State<Self> {
+        &self.state
+    }
+
+    fn register_components(&mut self, command: &C) -> Result<(), FrameworkError> {
+        let mut components = self.framework_components(command)?;
+        components.extend(command.components());
+        self.state.components_mut().register(components)
+    }
+
+    fn after_config(&mut self, config: ChildConfig) -> Result<(), FrameworkError> {
+        self.state.components_mut().after_config(&config)?;
+        self.config.set_once(config);
+        Ok(())
+    }
+
+    fn reload_config(&self) -> Result<(), FrameworkError> {
+        let command = self.command();
+        let loader = self.config_loader(command);
+        self.config.reload(&loader, command, &self.state)?;
+        Ok(())
+    }
+
+    fn crash_reports_dir(&self) -> Option<PathBuf> {
+        env::var_os(CHILD_REPORTS_ENV).map(PathBuf::from)
+    }
+
+    fn shutdown_deadline(&self, shutdown: Shutdown) -> Option<Duration> {
+        match shutdown {
+            Shutdown::Graceful => self.command().graceful_deadline(),
+            _ => Some(Duration::from_secs(5)),
+        }
+    }
+
+    fn thread_join_timeout(&self) -> Duration {
+        self.command().thread_join_timeout()
+    }
+}
+
+/// Run the application with the arguments given by the parent process, when
+/// invoked as a child process (and do nothing otherwise).
+pub fn run_child<C: ChildCommand>(app_cell: &'static AppCell<ChildApp<C>>, name: &str) {
+    if let Ok(args) = env::var(CHILD_ARGS_ENV) {
+        let args = args.split_whitespace().map(str::to_owned);
+        ChildApp::run(app_cell, std::iter::once(name.to_owned()).chain(args));
+    }
+}
+
+/// Create a runner for this test binary as a child process, running the
+/// application with the given arguments in its `child` test.
+pub fn child_runner(args: &str) -> CmdRunner {
+    let mut runner = CmdRunner::new(env::current_exe().unwrap());
+    runner
+        .env(CHILD_ARGS_ENV, args)
+        .args(["--exact", "child", "--nocapture", "--test-threads", "1"])
+        .capture_stdout()
+        .timeout(Duration::from_secs(30));
+    runner
+}
+
+/// Print a line to stdout for the parent process to read.
+pub fn report(line: &str) {
+    let mut stdout = io::stdout();
+    writeln!(stdout, "{}", line).unwrap();
+    stdout.flush().unwrap();
+}
+
+/// Skip the test harness's output until the given line is reported (the
+/// first one follows the harness's `test child ... ` on the same line).
+pub fn expect_report(process: &mut Process<'_>, expected: &str) {
+    loop {
+        let mut line = String::new();
+        let len = process.stdout().read_line(&mut line).unwrap();
+        assert_ne!(len, 0, "child exited before reporting {:?}", expected);
+
+        if line.trim_end().ends_with(expected) {
+            return;
+        }
+    }
+}
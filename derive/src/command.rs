@@ -5,10 +5,10 @@ use quote::quote;
 use synstructure::Structure;
 
 /// Custom derive for `abscissa_core::command::Command`
-pub fn derive_command(s: Structure<'_>) -> TokenStream {
+pub fn derive_command(mut s: Structure<'_>) -> TokenStream {
     let subcommand_usage = quote!();
 
-    s.gen_impl(quote! {
+    s.underscore_const(true).gen_impl(quote! {
         gen impl Command for @Self {
             #[doc = "Name of this program as a string"]
             fn name() -> &'static str {
@@ -42,8 +42,7 @@ mod tests {
                 struct MyCommand {}
             }
             expands to {
-                #[allow(non_upper_case_globals)]
-                const _DERIVE_Command_FOR_MyCommand: () = {
+                const _: () = {
                     impl Command for MyCommand {
                         #[doc = "Name of this program as a string"]
                         fn name() -> & 'static str {
@@ -77,8 +76,7 @@ mod tests {
                 }
             }
             expands to {
-                #[allow(non_upper_case_globals)]
-                const _DERIVE_Command_FOR_MyCommand: () = {
+                const _: () = {
                     impl Command for MyCommand {
                         #[doc = "Name of this program as a string"]
                         fn name() -> & 'static str {
@@ -2,17 +2,20 @@
 
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::quote;
-use syn::{DeriveInput, Lit, Meta, MetaList, MetaNameValue, NestedMeta};
+use syn::{DeriveInput, Error, Lit, LitStr, Meta, MetaList, MetaNameValue, NestedMeta};
 use synstructure::Structure;
 
 /// Custom derive for `abscissa_core::component::Component`
-pub fn derive_component(s: Structure<'_>) -> TokenStream {
-    let attrs = ComponentAttributes::from_derive_input(s.ast());
+pub fn derive_component(mut s: Structure<'_>) -> TokenStream {
+    let attrs = match ComponentAttributes::from_derive_input(s.ast()) {
+        Ok(attrs) => attrs,
+        Err(e) => return e.to_compile_error(),
+    };
     let name = &s.ast().ident;
     let abscissa_core = attrs.abscissa_core_crate();
     let dependency_methods = attrs.dependency_methods();
 
-    s.gen_impl(quote! {
+    s.underscore_const(true).gen_impl(quote! {
         gen impl<A> Component<A> for @Self
         where
             A: #abscissa_core::Application
@@ -48,7 +51,7 @@ struct ComponentAttributes {
 
 impl ComponentAttributes {
     /// Parse component attributes from custom derive input.
-    pub fn from_derive_input(input: &DeriveInput) -> Self {
+    pub fn from_derive_input(input: &DeriveInput) -> Result<Self, Error> {
         let mut core = false;
         let mut inject = Vec::new();
 
@@ -57,7 +60,7 @@ impl ComponentAttributes {
                 continue;
             }
 
-            match attr.parse_meta().expect("error parsing meta") {
+            match attr.parse_meta()? {
                 Meta::List(MetaList { nested, .. }) => {
                     for meta in &nested {
                         match meta {
@@ -65,17 +68,22 @@ impl ComponentAttributes {
                                 core = true
                             }
                             NestedMeta::Meta(Meta::NameValue { .. }) => {
-                                inject.push(InjectAttribute::from_nested_meta(meta))
+                                inject.push(InjectAttribute::from_nested_meta(meta)?)
+                            }
+                            _ => {
+                                return Err(Error::new_spanned(
+                                    meta,
+                                    "malformed `component` attribute",
+                                ))
                             }
-                            _ => panic!("malformed `component` attribute: {:?}", meta),
                         }
                     }
                 }
-                other => panic!("malformed `component` attribute: {:?}", other),
+                other => return Err(Error::new_spanned(other, "malformed `component` attribute")),
             };
         }
 
-        Self { core, inject }
+        Ok(Self { core, inject })
     }
 
     /// Ident for the `abscissa_core` crate.
@@ -88,25 +96,75 @@ impl ComponentAttributes {
         Ident::new(crate_name, Span::call_site())
     }
 
-    /// Generate `Component::dependencies()` and `register_dependencies()`
+    /// Generate `Component::dependencies()`, `optional_dependencies()`,
+    /// `version_requirements()`, and `register_dependencies()`
     pub fn dependency_methods(&self) -> TokenStream {
         if self.inject.is_empty() {
             return quote!();
         }
 
         let abscissa_core = self.abscissa_core_crate();
-        let ids = self
+        let (optional, required): (Vec<_>, Vec<_>) =
+            self.inject.iter().partition(|inject| inject.is_optional());
+
+        let dependencies = if required.is_empty() {
+            quote!()
+        } else {
+            let ids = required
+                .iter()
+                .map(|inject| inject.id_tokens(&abscissa_core));
+
+            quote! {
+                fn dependencies(&self) -> std::slice::Iter<'_, #abscissa_core::component::Id> {
+                    const DEPENDENCIES: &[#abscissa_core::component::Id] = &[#(#ids),*];
+                    DEPENDENCIES.iter()
+                }
+            }
+        };
+
+        let optional_dependencies = if optional.is_empty() {
+            quote!()
+        } else {
+            let ids = optional
+                .iter()
+                .map(|inject| inject.id_tokens(&abscissa_core));
+
+            quote! {
+                fn optional_dependencies(&self) -> std::slice::Iter<'_, #abscissa_core::component::Id> {
+                    const DEPENDENCIES: &[#abscissa_core::component::Id] = &[#(#ids),*];
+                    DEPENDENCIES.iter()
+                }
+            }
+        };
+
+        let requirements = self
             .inject
             .iter()
-            .map(|inject| inject.id_tokens(&abscissa_core));
+            .filter_map(|inject| inject.requirement_tokens(&abscissa_core))
+            .collect::<Vec<_>>();
+
+        let version_requirements = if requirements.is_empty() {
+            quote!()
+        } else {
+            quote! {
+                fn version_requirements(
+                    &self,
+                ) -> std::slice::Iter<'_, #abscissa_core::component::Requirement> {
+                    const REQUIREMENTS: &[#abscissa_core::component::Requirement] =
+                        &[#(#requirements),*];
+                    REQUIREMENTS.iter()
+                }
+            }
+        };
 
         let match_arms = self.inject.iter().map(|inject| inject.match_arm());
 
         quote! {
-            fn dependencies(&self) -> std::slice::Iter<'_, #abscissa_core::component::Id> {
-                const DEPENDENCIES: &[#abscissa_core::component::Id] = &[#(#ids),*];
-                DEPENDENCIES.iter()
-            }
+            #dependencies
+
+            #optional_dependencies
+
+            #version_requirements
 
             fn register_dependency(
                 &mut self,
@@ -122,47 +180,111 @@ impl ComponentAttributes {
     }
 }
 
-/// Attribute declaring a dependency which should be injected
+/// Attribute declaring a dependency which should be injected, e.g.
+/// `inject = "init_foo(my_crate::Foo)"`.
+///
+/// The component ID may be preceded by `optional` if the dependency should
+/// only be injected if it's registered, and followed by a semver requirement
+/// on its version, e.g. `inject = "init_foo(optional my_crate::Foo >=0.7)"`.
 #[derive(Debug)]
-pub struct InjectAttribute(String);
+pub struct InjectAttribute {
+    /// Callback which receives the dependency
+    callback: Ident,
+
+    /// Is the dependency optional?
+    optional: bool,
+
+    /// Component ID of the dependency
+    component_id: String,
+
+    /// Semver requirement on the version of the dependency (if any)
+    version_req: Option<String>,
+}
 
 impl InjectAttribute {
     /// Parse an [`InjectAttribute`] from [`NestedMeta`].
-    pub fn from_nested_meta(meta: &NestedMeta) -> Self {
+    pub fn from_nested_meta(meta: &NestedMeta) -> Result<Self, Error> {
         match meta {
             NestedMeta::Meta(Meta::NameValue(MetaNameValue {
                 path,
                 lit: Lit::Str(lit_str),
                 ..
-            })) if path.is_ident("inject") => Self(lit_str.value()),
-            _ => panic!("malformed `component` attribute: {:?}", meta),
+            })) if path.is_ident("inject") => Self::parse(lit_str),
+            _ => Err(Error::new_spanned(meta, "malformed `component` attribute")),
         }
     }
 
-    /// Parse the callback and component ID of the value of an inject attribute.
-    fn parse_value(&self) -> (&str, &str) {
-        assert!(
-            self.0.ends_with(')'),
-            "expected {} to end with ')'",
-            &self.0
-        );
+    /// Parse the value of an inject attribute, reporting errors (including
+    /// invalid version requirements) at its span.
+    pub fn parse(lit: &LitStr) -> Result<Self, Error> {
+        let value = lit.value();
+
+        let (callback, dependency) = value
+            .strip_suffix(')')
+            .and_then(|value| value.split_once('('))
+            .filter(|(_, dependency)| !dependency.contains('('))
+            .ok_or_else(|| {
+                Error::new(
+                    lit.span(),
+                    format!("expected `callback(component::Id)`, found {:?}", value),
+                )
+            })?;
+
+        let callback = syn::parse_str::<Ident>(callback).map_err(|_| {
+            Error::new(lit.span(), format!("invalid callback name: {:?}", callback))
+        })?;
+
+        let dependency = dependency.trim();
+
+        let (optional, dependency) = match dependency.strip_prefix("optional ") {
+            Some(dependency) => (true, dependency.trim_start()),
+            None => (false, dependency),
+        };
 
-        let mut paren_parts = self.0[..(self.0.len() - 1)].split('(');
-        let callback = paren_parts.next().unwrap();
-        let component_id = paren_parts.next().unwrap();
-        assert_eq!(paren_parts.next(), None);
+        let (component_id, version_req) = match dependency.find(char::is_whitespace) {
+            Some(pos) => (&dependency[..pos], Some(dependency[pos..].trim())),
+            None => (dependency, None),
+        };
 
-        (callback, component_id)
+        if component_id.is_empty() {
+            return Err(Error::new(lit.span(), "missing component ID"));
+        }
+
+        if let Some(version_req) = version_req {
+            if let Err(e) = semver::VersionReq::parse(version_req) {
+                return Err(Error::new(
+                    lit.span(),
+                    format!("invalid version requirement {:?}: {}", version_req, e),
+                ));
+            }
+        }
+
+        Ok(Self {
+            callback,
+            optional,
+            component_id: component_id.to_owned(),
+            version_req: version_req.map(ToOwned::to_owned),
+        })
     }
 
     /// Get the callback associated with this inject attribute
-    pub fn callback(&self) -> Ident {
-        Ident::new(self.parse_value().0, Span::call_site())
+    pub fn callback(&self) -> &Ident {
+        &self.callback
+    }
+
+    /// Is the dependency optional?
+    pub fn is_optional(&self) -> bool {
+        self.optional
     }
 
     /// Get the component ID associated with this inject attribute
     pub fn component_id(&self) -> &str {
-        self.parse_value().1
+        &self.component_id
+    }
+
+    /// Get the semver requirement on the version of the dependency (if any)
+    pub fn version_req(&self) -> Option<&str> {
+        self.version_req.as_deref()
     }
 
     /// Get the tokens representing a component ID
@@ -171,6 +293,14 @@ impl InjectAttribute {
         quote! { #abscissa_core::component::Id::new(#component_id) }
     }
 
+    /// Get the tokens representing the requirement on the version of the
+    /// dependency (if any)
+    pub fn requirement_tokens(&self, abscissa_core: &Ident) -> Option<TokenStream> {
+        let id = self.id_tokens(abscissa_core);
+        let version_req = self.version_req()?;
+        Some(quote! { #abscissa_core::component::Requirement::new(#id, #version_req) })
+    }
+
     /// Get match arm that invokes a concrete callback
     pub fn match_arm(&self) -> TokenStream {
         let id_str = self.component_id();
@@ -197,8 +327,7 @@ mod tests {
                 struct MyComponent {}
             }
             expands to {
-                #[allow(non_upper_case_globals)]
-                const _DERIVE_Component_A_FOR_MyComponent: () = {
+                const _: () = {
                     impl<A> Component<A> for MyComponent
                     where
                         A: abscissa_core::Application
@@ -220,4 +349,42 @@ mod tests {
             no_build // tests the code compiles are in the `abscissa` crate
         }
     }
+
+    fn parse_inject(value: &str) -> Result<InjectAttribute, Error> {
+        InjectAttribute::parse(&LitStr::new(value, Span::call_site()))
+    }
+
+    #[test]
+    fn parse_inject_attribute() {
+        let inject = parse_inject("init_foo(my_crate::Foo)").unwrap();
+        assert_eq!(inject.callback().to_string(), "init_foo");
+        assert_eq!(inject.component_id(), "my_crate::Foo");
+        assert!(!inject.is_optional());
+        assert_eq!(inject.version_req(), None);
+
+        let inject = parse_inject("init_foo(optional my_crate::Foo >=0.7, <0.9)").unwrap();
+        assert_eq!(inject.component_id(), "my_crate::Foo");
+        assert!(inject.is_optional());
+        assert_eq!(inject.version_req(), Some(">=0.7, <0.9"));
+    }
+
+    #[test]
+    fn parse_malformed_inject_attribute() {
+        let err = parse_inject("init_foo my_crate::Foo").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "expected `callback(component::Id)`, found \"init_foo my_crate::Foo\""
+        );
+
+        let err = parse_inject("init foo(my_crate::Foo)").unwrap_err();
+        assert_eq!(err.to_string(), "invalid callback name: \"init foo\"");
+
+        let err = parse_inject("init_foo()").unwrap_err();
+        assert_eq!(err.to_string(), "missing component ID");
+
+        let err = parse_inject("init_foo(my_crate::Foo >=banana)").unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("invalid version requirement \">=banana\": "));
+    }
 }
@@ -5,10 +5,13 @@
 
 mod command;
 mod component;
+mod overrides;
 mod runnable;
 
 use synstructure::decl_derive;
 
 decl_derive!([Command] => command::derive_command);
 decl_derive!([Component, attributes(component)] => component::derive_component);
+decl_derive!([Override, attributes(config)] => overrides::derive_override);
 decl_derive!([Runnable] => runnable::derive_runnable);
+decl_derive!([TryRunnable, attributes(runnable)] => runnable::derive_try_runnable);
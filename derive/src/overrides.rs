@@ -0,0 +1,263 @@
+//! Custom derive support for `abscissa_core::config::Override`.
+
+use proc_macro2::{Ident, TokenStream};
+use quote::{quote, quote_spanned};
+use syn::{
+    spanned::Spanned, Attribute, Error, Field, GenericArgument, Lit, LitStr, Meta, MetaList,
+    MetaNameValue, NestedMeta, PathArguments, Type,
+};
+use synstructure::Structure;
+
+/// Custom derive for `abscissa_core::config::Override`
+pub fn derive_override(mut s: Structure<'_>) -> TokenStream {
+    match OverrideAttributes::from_structure(&s) {
+        Ok(attrs) => {
+            let config_type = &attrs.config_type;
+            let overrides = &attrs.overrides;
+
+            s.underscore_const(true).gen_impl(quote! {
+                gen impl abscissa_core::config::Override<#config_type> for @Self {
+                    #[allow(clippy::useless_conversion)]
+                    fn override_config(
+                        &self,
+                        mut config: #config_type,
+                    ) -> Result<#config_type, abscissa_core::FrameworkError> {
+                        #(#overrides)*
+                        Ok(config)
+                    }
+                }
+            })
+        }
+        Err(e) => e.to_compile_error(),
+    }
+}
+
+/// Parsed `#[config(...)]` attributes
+struct OverrideAttributes {
+    /// Configuration type to override (from `#[config(type = "...")]` on
+    /// the struct)
+    config_type: syn::Path,
+
+    /// Statements which override configuration values (from
+    /// `#[config(path = "...")]` on the struct's fields)
+    overrides: Vec<TokenStream>,
+}
+
+impl OverrideAttributes {
+    /// Parse the attributes of the struct and its fields.
+    fn from_structure(s: &Structure<'_>) -> Result<Self, Error> {
+        let ast = s.ast();
+        let mut config_type = None;
+
+        for attr in &ast.attrs {
+            if let Some(value) = parse_attribute(attr, "type")? {
+                config_type = Some(value.parse::<syn::Path>()?);
+            }
+        }
+
+        let config_type = config_type.ok_or_else(|| {
+            Error::new(
+                ast.ident.span(),
+                "missing `#[config(type = \"...\")]` attribute naming the configuration type",
+            )
+        })?;
+
+        let variant = match s.variants() {
+            [variant] if matches!(ast.data, syn::Data::Struct(_)) => variant,
+            _ => {
+                return Err(Error::new(
+                    ast.ident.span(),
+                    "`Override` can only be derived for structs",
+                ))
+            }
+        };
+
+        let mut overrides = vec![];
+
+        for binding in variant.bindings() {
+            let field = binding.ast();
+
+            for attr in &field.attrs {
+                if let Some(path) = parse_attribute(attr, "path")? {
+                    overrides.push(override_statement(field, &path)?);
+                }
+            }
+        }
+
+        Ok(Self {
+            config_type,
+            overrides,
+        })
+    }
+}
+
+/// Parse the value of the `#[config(<name> = "...")]` attribute, if the
+/// given attribute is a `config` attribute.
+fn parse_attribute(attr: &Attribute, name: &str) -> Result<Option<LitStr>, Error> {
+    if !attr.path.is_ident("config") {
+        return Ok(None);
+    }
+
+    let nested = match attr.parse_meta()? {
+        Meta::List(MetaList { nested, .. }) => nested,
+        other => return Err(Error::new(other.span(), "malformed `config` attribute")),
+    };
+
+    let mut value = None;
+
+    for meta in &nested {
+        match meta {
+            NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                path,
+                lit: Lit::Str(lit_str),
+                ..
+            })) if path.is_ident(name) => value = Some(lit_str.clone()),
+            _ => {
+                return Err(Error::new(
+                    meta.span(),
+                    format!("expected `{} = \"...\"`", name),
+                ))
+            }
+        }
+    }
+
+    Ok(value)
+}
+
+/// Generate a statement which overrides the value at the given key path of
+/// the configuration with the value of the given field, if it was set.
+fn override_statement(field: &Field, path: &LitStr) -> Result<TokenStream, Error> {
+    let span = path.span();
+    let member = field
+        .ident
+        .as_ref()
+        .ok_or_else(|| Error::new(field.span(), "`Override` requires named fields"))?;
+
+    let mut keys = vec![];
+
+    for key in path.value().split('.') {
+        match syn::parse_str::<Ident>(key) {
+            Ok(mut ident) => {
+                ident.set_span(span);
+                keys.push(ident);
+            }
+            Err(_) => {
+                return Err(Error::new(
+                    span,
+                    format!("invalid config key path: `{}`", path.value()),
+                ))
+            }
+        }
+    }
+
+    let target = quote_spanned!(span=> config.#(#keys).*);
+
+    let statement = match FieldKind::of(&field.ty) {
+        Some(FieldKind::Option) => quote_spanned! {span=>
+            if let Some(value) = &self.#member {
+                #target = ::core::clone::Clone::clone(value).into();
+            }
+        },
+        Some(FieldKind::Vec) => quote_spanned! {span=>
+            if !self.#member.is_empty() {
+                #target = ::core::clone::Clone::clone(&self.#member).into();
+            }
+        },
+        Some(FieldKind::Bool) => quote_spanned! {span=>
+            if self.#member {
+                #target = true;
+            }
+        },
+        None => {
+            return Err(Error::new(
+                field.ty.span(),
+                "overridden fields must be `Option<T>`, `Vec<T>`, or `bool`",
+            ))
+        }
+    };
+
+    Ok(statement)
+}
+
+/// Kinds of fields which can override configuration values
+enum FieldKind {
+    /// `Option<T>`: overrides the value if present
+    Option,
+
+    /// `Vec<T>`: overrides the value if nonempty
+    Vec,
+
+    /// `bool`: sets the value to `true` if the flag was passed
+    Bool,
+}
+
+impl FieldKind {
+    /// Determine the kind of field from its type (if supported).
+    fn of(ty: &Type) -> Option<Self> {
+        let segment = match ty {
+            Type::Path(type_path) if type_path.qself.is_none() => type_path.path.segments.last()?,
+            _ => return None,
+        };
+
+        let has_type_argument = match &segment.arguments {
+            PathArguments::AngleBracketed(args) => {
+                args.args.len() == 1 && matches!(args.args.first(), Some(GenericArgument::Type(_)))
+            }
+            _ => false,
+        };
+
+        if segment.ident == "bool" && segment.arguments.is_empty() {
+            Some(FieldKind::Bool)
+        } else if segment.ident == "Option" && has_type_argument {
+            Some(FieldKind::Option)
+        } else if segment.ident == "Vec" && has_type_argument {
+            Some(FieldKind::Vec)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use synstructure::test_derive;
+
+    #[test]
+    fn derive_override_struct() {
+        test_derive! {
+            derive_override {
+                #[config(type = "MyConfig")]
+                struct MyCommand {
+                    #[config(path = "server.port")]
+                    port: Option<u16>,
+
+                    #[config(path = "verbose")]
+                    verbose: bool,
+
+                    other: bool,
+                }
+            }
+            expands to {
+                const _: () = {
+                    impl abscissa_core::config::Override<MyConfig> for MyCommand {
+                        #[allow(clippy::useless_conversion)]
+                        fn override_config(
+                            &self,
+                            mut config: MyConfig,
+                        ) -> Result<MyConfig, abscissa_core::FrameworkError> {
+                            if let Some(value) = &self.port {
+                                config.server.port = ::core::clone::Clone::clone(value).into();
+                            }
+                            if self.verbose {
+                                config.verbose = true;
+                            }
+                            Ok(config)
+                        }
+                    }
+                };
+            }
+            no_build // tests the code compiles are in the `abscissa` crate
+        }
+    }
+}
@@ -1,12 +1,14 @@
+use proc_macro2::TokenStream;
 use quote::quote;
+use syn::{DeriveInput, Error, Lit, Meta, MetaList, MetaNameValue, NestedMeta, Path};
 
 /// Custom derive for `abscissa_core::runnable::Runnable`
-pub fn derive_runnable(s: synstructure::Structure<'_>) -> proc_macro2::TokenStream {
+pub fn derive_runnable(mut s: synstructure::Structure<'_>) -> TokenStream {
     let body = s.each(|bi| {
         quote! { #bi.run() }
     });
 
-    s.gen_impl(quote! {
+    s.underscore_const(true).gen_impl(quote! {
         gen impl Runnable for @Self {
             fn run(&self) {
                 match *self { #body }
@@ -15,6 +17,68 @@ pub fn derive_runnable(s: synstructure::Structure<'_>) -> proc_macro2::TokenStre
     })
 }
 
+/// Custom derive for `abscissa_core::runnable::TryRunnable`
+pub fn derive_try_runnable(mut s: synstructure::Structure<'_>) -> TokenStream {
+    let error = match error_type(s.ast()) {
+        Ok(error) => error,
+        Err(e) => return e.to_compile_error(),
+    };
+    let body = s.each(|bi| {
+        quote! { TryRunnable::try_run(#bi).map_err(::core::convert::Into::into) }
+    });
+
+    s.underscore_const(true).gen_impl(quote! {
+        gen impl TryRunnable for @Self {
+            type Error = #error;
+
+            fn try_run(&self) -> ::core::result::Result<(), #error> {
+                match *self { #body }
+            }
+        }
+    })
+}
+
+/// Parse the error type from the `#[runnable(error = "...")]` attribute,
+/// defaulting to `abscissa_core::FrameworkError`.
+fn error_type(input: &DeriveInput) -> Result<TokenStream, Error> {
+    let mut error = None;
+
+    for attr in &input.attrs {
+        if !attr.path.is_ident("runnable") {
+            continue;
+        }
+
+        match attr.parse_meta()? {
+            Meta::List(MetaList { nested, .. }) => {
+                for meta in &nested {
+                    match meta {
+                        NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                            path,
+                            lit: Lit::Str(lit),
+                            ..
+                        })) if path.is_ident("error") => {
+                            let path = lit.parse::<Path>().map_err(|e| {
+                                Error::new_spanned(lit, format!("invalid error type: {}", e))
+                            })?;
+
+                            error = Some(path);
+                        }
+                        _ => {
+                            return Err(Error::new_spanned(meta, "malformed `runnable` attribute"))
+                        }
+                    }
+                }
+            }
+            other => return Err(Error::new_spanned(other, "malformed `runnable` attribute")),
+        }
+    }
+
+    Ok(match error {
+        Some(error) => quote!(#error),
+        None => quote!(abscissa_core::FrameworkError),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -31,8 +95,7 @@ mod tests {
                 }
             }
             expands to {
-                #[allow(non_upper_case_globals)]
-                const _DERIVE_Runnable_FOR_MyRunnable: () = {
+                const _: () = {
                     impl Runnable for MyRunnable {
                         fn run(&self) {
                             match *self {
@@ -53,4 +116,63 @@ mod tests {
             no_build // tests the code compiles are in the `abscissa` crate
         }
     }
+
+    #[test]
+    fn derive_try_runnable_on_enum() {
+        test_derive! {
+            derive_try_runnable {
+                #[runnable(error = "crate::Error")]
+                enum MyRunnable {
+                    A(VariantA),
+                    B(VariantB),
+                }
+            }
+            expands to {
+                const _: () = {
+                    impl TryRunnable for MyRunnable {
+                        type Error = crate::Error;
+
+                        fn try_run(&self) -> ::core::result::Result<(), crate::Error> {
+                            match *self {
+                                MyRunnable::A(ref __binding_0,) => {
+                                    { TryRunnable::try_run(__binding_0).map_err(::core::convert::Into::into) }
+                                }
+                                MyRunnable::B(ref __binding_0,) => {
+                                    { TryRunnable::try_run(__binding_0).map_err(::core::convert::Into::into) }
+                                }
+                            }
+                        }
+                    }
+                };
+            }
+            no_build // tests the code compiles are in the `abscissa` crate
+        }
+    }
+
+    #[test]
+    fn malformed_runnable_attribute() {
+        let error_type = |attr: &str| {
+            let input = format!("{} enum MyRunnable {{ A(VariantA) }}", attr);
+            error_type(&syn::parse_str(&input).unwrap()).map_err(|e| e.to_string())
+        };
+
+        assert_eq!(
+            error_type("#[runnable(error = \"crate::Error\")]")
+                .unwrap()
+                .to_string(),
+            "crate :: Error"
+        );
+        assert_eq!(
+            error_type("#[runnable(error = \"not a path\")]").unwrap_err(),
+            "invalid error type: unexpected token"
+        );
+        assert_eq!(
+            error_type("#[runnable(err = \"crate::Error\")]").unwrap_err(),
+            "malformed `runnable` attribute"
+        );
+        assert_eq!(
+            error_type("#[runnable = \"crate::Error\"]").unwrap_err(),
+            "malformed `runnable` attribute"
+        );
+    }
 }